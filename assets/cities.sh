@@ -0,0 +1,37 @@
+#!/bin/sh
+# Downloads the GeoNames city database (CC BY 4.0) and trims it for the
+# package: `cargo deb` installs it to /usr/share/ice/cities15000.txt, so
+# the offline city search works on the devices without the connection.
+#
+# Usage: sh assets/cities.sh [OUTPUT]   (default: target/cities15000.txt)
+#
+# The columns unused by the program are emptied and the alternate names
+# in the scripts other than Latin and Cyrillic are dropped, the format of
+# the dump is kept.
+
+set -eu
+
+URL="https://download.geonames.org/export/dump/cities15000.zip"
+OUTPUT="${1:-target/cities15000.txt}"
+
+TMP="$(mktemp -d)"
+trap 'rm -rf "$TMP"' EXIT
+
+curl -fsSL -o "$TMP/cities.zip" "$URL"
+mkdir -p "$(dirname "$OUTPUT")"
+
+# Bytes are checked (LC_ALL=C): UTF-8 lead bytes up to 0xD4 are Latin,
+# Greek and Cyrillic, 0xE1 0xB8-0xBB is Latin Extended Additional
+unzip -p "$TMP/cities.zip" cities15000.txt | LC_ALL=C awk -F '\t' -v OFS='\t' '
+{
+    n = split($4, names, ",")
+    alt = ""
+    for (i = 1; i <= n; i++) {
+        if (names[i] ~ /[\325-\340\342-\377]/ || names[i] ~ /\341[^\270-\273]/)
+            continue
+        alt = alt (alt == "" ? "" : ",") names[i]
+    }
+    print $1, $2, $3, alt, $5, $6, "", "", $9, "", "", "", "", "", $15
+}' >"$OUTPUT"
+
+echo "$(wc -l <"$OUTPUT") cities written to $OUTPUT"
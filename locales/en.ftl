@@ -0,0 +1,356 @@
+# English translation of Ice
+
+## Weather conditions (OpenWeatherMap codes)
+
+weather-thunderstorm-light-rain = thunderstorm with light rain
+weather-thunderstorm-rain = thunderstorm with rain
+weather-thunderstorm-heavy-rain = thunderstorm with heavy rain
+weather-light-thunderstorm = light thunderstorm
+weather-thunderstorm = thunderstorm
+weather-heavy-thunderstorm = heavy thunderstorm
+weather-ragged-thunderstorm = ragged thunderstorm
+weather-thunderstorm-light-drizzle = thunderstorm with light drizzle
+weather-thunderstorm-drizzle = thunderstorm with drizzle
+weather-thunderstorm-heavy-drizzle = thunderstorm with heavy drizzle
+weather-light-drizzle = light drizzle
+weather-drizzle = drizzle
+weather-heavy-drizzle = heavy drizzle
+weather-shower-rain-drizzle = shower rain and drizzle
+weather-heavy-shower-drizzle = heavy shower rain and drizzle
+weather-light-rain = light rain
+weather-moderate-rain = moderate rain
+weather-heavy-rain = heavy intensity rain
+weather-very-heavy-rain = very heavy rain
+weather-heavy-shower-rain = heavy shower rain
+weather-freezing-rain = freezing rain
+weather-light-shower-rain = light shower rain
+weather-shower-rain = shower rain
+weather-ragged-shower-rain = ragged shower rain
+weather-light-snow = light snow
+weather-snow = snow
+weather-heavy-snow = heavy snow
+weather-sleet = sleet
+weather-light-sleet = light shower sleet
+weather-rain-and-snow = rain and snow
+weather-fog = fog
+weather-smoke = smoke
+weather-haze = haze
+weather-dust-whirls = sand/dust whirls
+weather-sand = sand
+weather-dust = dust
+weather-volcanic-ash = volcanic ash
+weather-squalls = squalls
+weather-tornado = tornado
+weather-clear-sky = clear sky
+weather-few-clouds = few clouds
+weather-scattered-clouds = scattered clouds
+weather-broken-clouds = broken clouds
+weather-overcast = overcast clouds
+weather-unknown = unknown weather
+
+## Wind directions (where the wind blows from)
+
+wind-n = N
+wind-nne = NNE
+wind-ne = NE
+wind-ene = ENE
+wind-e = E
+wind-ese = ESE
+wind-se = SE
+wind-sse = SSE
+wind-s = S
+wind-ssw = SSW
+wind-sw = SW
+wind-wsw = WSW
+wind-w = W
+wind-wnw = WNW
+wind-nw = NW
+wind-nnw = NNW
+
+## Errors of the weather API
+
+error-request-url = Invalid request URL '{ $url }' ({ $error })
+error-request = Failed to get the data from the server. Check the network connection and the request { $request } ({ $error })
+error-response = The server returned an error for the request { $request } ({ $error })
+error-json = Failed to parse the JSON from the server ({ $error })
+
+## Errors of the config
+
+error-appid = Invalid OpenWeatherMap API key: 32 hexadecimal characters are required
+error-no-location = The location is not selected and its autodetection is disabled
+error-refresh-interval = The weather update interval must be from { $min } to { $max } minutes
+error-config-read = Failed to read the config ({ $error }). Check that it exists and is readable
+error-config-parse =
+    Failed to parse the config. Check its syntax.
+
+    { $error }
+error-config-serialize =
+    Failed to serialize the config. Check the values.
+
+    { $error }
+error-config-write = Failed to write the config ({ $error }). Check the file system permissions.
+
+## Time and units
+
+time-just-now = just now
+time-minutes-ago = { $minutes } min ago
+time-hours-ago = { $hours } h ago
+time-days-ago = { $days } d ago
+time-days-duration = { $days } d { $time }
+unit-meters-per-second = m/s
+unit-mph = mph
+
+## Weather pages
+
+loading = Loading...
+weather-hourly-placeholder = The hourly forecast will be here
+current-feels-like = Feels like { $temp }
+current-gust = gusts up to { $speed }
+current-min-max = Min / max
+current-pressure = Pressure
+current-humidity = Humidity
+current-visibility = Visibility
+current-visibility-max = over 10 km
+current-clouds = Cloudiness
+current-wind = Wind
+current-sunrise = Sunrise
+current-sunset = Sunset
+current-observed = Observed
+unit-hpa = { $pressure } hPa
+unit-mmhg = { $pressure } mmHg
+unit-km = { $distance } km
+
+## Daily forecast
+
+daily-loading = Loading the forecast...
+daily-today = Today
+daily-tomorrow = Tomorrow
+weekday-mon = Monday
+weekday-tue = Tuesday
+weekday-wed = Wednesday
+weekday-thu = Thursday
+weekday-fri = Friday
+weekday-sat = Saturday
+weekday-sun = Sunday
+daily-precipitation = Precipitation { $pop }%
+daily-precipitation-volume = Precipitation { $pop }%, { $volume } mm
+
+## Widgets
+
+connectivity-full = Internet
+connectivity-limited = No Internet
+connectivity-portal = Login required
+connectivity-none = No network
+connectivity-unknown = Network ?
+keyboard-backspace = Delete
+keyboard-space = Space
+
+## Main screen
+
+refresh = Refresh
+refreshing = Refreshing...
+weather-updated = Updated { $ago }
+weather-no-data = No weather data
+weather-attribution = Weather data provided by OpenWeatherMap
+uptime = Uptime: { $time }
+nav-location = Location
+nav-forecast = Forecast
+nav-network = Network
+nav-system = System
+nav-back = Back
+action-retry = Retry
+action-settings = Settings
+error-unknown-location = Unknown location
+provisioning-done = The device is connected to the network, the settings are saved
+provisioning-no-city = The device is connected to the network and the API key is saved, but the location isn't: { $error }. Set it in the settings
+
+## Modal windows
+
+close = Close
+cancel = Cancel
+none = none
+error-title = Error
+location-search-placeholder = City or postal code
+location-search = Search
+location-not-found = Nothing found
+provisioning-title = Setup
+provisioning-connect = No network connection. To set up the device, connect your phone to the Wi-Fi network:
+provisioning-password = Password: { $password }
+provisioning-open-page = and open the page in the browser
+provisioning-connecting = Connecting to the network “{ $ssid }”...
+power-title = Power
+power-off = Shut down
+power-reboot = Reboot
+power-exit = Exit to console
+power-off-confirm = Shut down the device?
+power-reboot-confirm = Reboot the device?
+power-exit-confirm = Close the program and exit to the console?
+power-denied = Not permitted (polkit)
+power-unsupported = Not supported
+about-unknown = unknown
+about-device = Device
+about-os = OS
+about-kernel = Kernel
+about-addresses = IP addresses
+about-weather = Weather
+about-cities = Cities
+about-icons = Icons
+about-build = Build
+about-build-info = { $commit } from { $date }
+about-license = License
+about-sources = Data sources
+
+## Settings
+
+settings-saved = Settings saved
+settings-save = Save
+settings-reset = Reset
+settings-done = Done
+settings-edit = Change
+settings-auto = Auto
+settings-units = Units
+settings-location-auto = Autodetect
+settings-location-manual = Manual
+settings-location-none = not selected
+settings-location-select = Select
+settings-appid = API key
+settings-appid-title = OpenWeatherMap API key
+settings-geoip = Primary IP service
+settings-refresh-interval = Weather update
+settings-minutes = { $minutes } min
+settings-hours = { $hours } h
+settings-theme = Theme
+settings-theme-light = Light
+settings-theme-dark = Dark
+settings-time-format = Time format
+settings-time-24h = 24 hours
+settings-time-12h = 12 hours
+settings-language = Language
+
+## System monitor
+
+system-loading = Collecting the system information...
+system-cores = Cores: { $cores }
+system-memory = RAM
+system-memory-details = { $used } / { $total }, swap { $swap }
+system-temperature = Temperature
+system-throttled = CPU frequency is throttled
+system-disk = Disk
+system-disk-details = { $mount }: { $available } free of { $total }
+system-uptime = System uptime: { $time }
+system-cpu-freq = CPU frequency: { $freq } MHz
+system-under-voltage = Under-voltage detected!
+system-under-voltage-occurred = Under-voltage has occurred
+unit-kib = { $size } KiB
+unit-mib = { $size } MiB
+unit-gib = { $size } GiB
+unit-tib = { $size } TiB
+
+## Network
+
+network-connecting = Connecting to { $ssid }...
+network-connected = Connected to { $ssid }
+network-wrong-password = Wrong password
+network-timeout = Could not connect to { $ssid } in time
+network-failed = Failed to connect to { $ssid } (code { $reason })
+network-gateway = Gateway: { $gateway }
+network-disconnect = Disconnect
+network-no-connection = Not connected
+network-available = Available
+network-saved = Saved
+network-scanning = Scanning...
+network-hidden = Hidden network
+network-hidden-short = hidden network
+network-in-use = connected
+network-no-saved = No saved networks
+network-autoconnect-on = Auto: on
+network-autoconnect-off = Auto: off
+network-password = Password
+network-new-password = New password: { $id }
+network-connect = Connect
+network-connect-saved = Connect
+network-forget = Forget
+network-ssid = Network name
+unit-ghz = { $frequency } GHz
+
+## Power menu errors
+
+error-power-denied-poweroff = Not permitted to shut down. Allow this action to the user of the program in the polkit rules
+error-power-denied-reboot = Not permitted to reboot. Allow this action to the user of the program in the polkit rules
+error-power-denied-exit = Not permitted to exit to the console. Allow this action to the user of the program in the polkit rules
+error-power-poweroff = Failed to request the shutdown ({ $error })
+error-power-reboot = Failed to request the reboot ({ $error })
+error-power-exit = Failed to request the exit to the console ({ $error })
+
+## Location
+
+location-source-manual = manual
+location-source-cache = cache
+error-location-disabled = The location is not set and its autodetection is disabled in the config
+error-location =
+    Failed to detect the location:
+    { $errors }
+error-location-no-coords = the coordinates are not set
+error-location-cache-outdated = the cache is outdated
+
+## IP geolocation
+
+error-geoip-ipinfo = Invalid coordinates in the ipinfo.io response: { $loc }
+error-geoip-ipapi = ipapi.co returned no coordinates ({ $reason })
+error-geoip-db = Failed to read the GeoIP database { $path } ({ $error })
+error-geoip-not-found = The address { $ip } is not found in the GeoIP database ({ $error })
+error-geoip-no-location = No coordinates of the address { $ip } in the GeoIP database
+error-geoip-no-latitude = No latitude in the GeoIP database
+error-geoip-no-longitude = No longitude in the GeoIP database
+error-geoip-no-ip = The public IP address is unknown: set `ip` or `ip_url` of the MaxMind provider in the config
+error-geoip-no-providers = No IP geolocation services are configured
+
+## GPS
+
+error-gps-timeout = The GPS receiver did not get the location in { $timeout } s
+error-gpsd = Failed to connect to gpsd ({ $addr }): { $error }
+error-gpsd-closed = gpsd closed the connection without the location
+
+## Wi-Fi
+
+error-wifi-no-adapter = No Wi-Fi adapter found
+error-wifi-security = This Wi-Fi security type is not supported
+error-wifi-no-password = Enter the Wi-Fi password
+error-wifi-password-length = The Wi-Fi password must take 8 to 63 bytes (a Latin letter takes 1 byte, a Cyrillic one takes 2) or consist of 64 hex digits
+error-wifi-password-short = The Wi-Fi password must be at least 8 characters long
+error-wifi-no-ssid = Enter the Wi-Fi network name
+error-wifi-open = The Wi-Fi network is not password protected
+error-wifi-priority = The priority must be from { $min } to { $max }
+error-hotspot = Failed to start the hotspot ({ $status })
+
+## System information
+
+error-proc-stat-line = Invalid /proc/stat line: { $line }
+error-proc-stat = No CPU load in /proc/stat
+error-proc-meminfo = No memory information in /proc/meminfo
+error-proc-uptime = Invalid /proc/uptime data: { $uptime }
+error-disk = Failed to get the free space on { $mount } ({ $error })
+error-addresses = Failed to get the IP addresses ({ $error })
+error-read = Failed to read { $path } ({ $error })
+
+## Cities
+
+error-cities = Failed to read the cities database ({ $error })
+
+## Setup page
+
+setup-title = Ice setup
+setup-ssid = Wi-Fi network name
+setup-password = Password
+setup-city = City
+setup-appid = OpenWeatherMap API key
+setup-save = Save
+setup-done-title = Settings received
+setup-done = Ice is connecting to the “{ $ssid }” network. The hotspot will be turned off.
+setup-wrong-password = Wrong password of the “{ $ssid }” network
+setup-timeout = The “{ $ssid }” network did not respond in time
+setup-failed = Failed to connect to the “{ $ssid }” network (code { $reason })
+error-city-not-found = City “{ $city }” not found
+error-request-closed = The connection was closed before the end of the request
+error-request-too-large = The request is too large
+error-setup-page = Failed to start the setup page (port { $port }): { $error }
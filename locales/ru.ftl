@@ -0,0 +1,356 @@
+# Russian translation of Ice
+
+## Weather conditions (OpenWeatherMap codes)
+
+weather-thunderstorm-light-rain = гроза с небольшим дождём
+weather-thunderstorm-rain = гроза с дождём
+weather-thunderstorm-heavy-rain = гроза с сильным дождём
+weather-light-thunderstorm = небольшая гроза
+weather-thunderstorm = гроза
+weather-heavy-thunderstorm = сильная гроза
+weather-ragged-thunderstorm = рваная гроза
+weather-thunderstorm-light-drizzle = гроза с лёгким моросящим дождём
+weather-thunderstorm-drizzle = гроза с моросящим дождём
+weather-thunderstorm-heavy-drizzle = гроза с сильным моросящим дождём
+weather-light-drizzle = небольшой моросящий дождь
+weather-drizzle = моросящий дождь
+weather-heavy-drizzle = сильный моросящий дождь
+weather-shower-rain-drizzle = дождь и морось
+weather-heavy-shower-drizzle = сильный моросящий ливень
+weather-light-rain = дождь
+weather-moderate-rain = умеренный дождь
+weather-heavy-rain = сильный дождь
+weather-very-heavy-rain = очень сильный дождь
+weather-heavy-shower-rain = сильный ливень
+weather-freezing-rain = ледяной дождь
+weather-light-shower-rain = небольшой ливень
+weather-shower-rain = ливень
+weather-ragged-shower-rain = неровный дождь
+weather-light-snow = небольшой снег
+weather-snow = снег
+weather-heavy-snow = сильный снег
+weather-sleet = дождь со снегом
+weather-light-sleet = лёгкий дождь со снегом
+weather-rain-and-snow = снег с дождём
+weather-fog = туман
+weather-smoke = дым
+weather-haze = дымка
+weather-dust-whirls = вихри песка/пыли
+weather-sand = песок
+weather-dust = пыль
+weather-volcanic-ash = вулканический пепел
+weather-squalls = шторм
+weather-tornado = торнадо
+weather-clear-sky = ясно
+weather-few-clouds = небольшая облачность
+weather-scattered-clouds = рассеянные облака
+weather-broken-clouds = средняя облачность
+weather-overcast = пасмурно
+weather-unknown = погода неизвестна
+
+## Wind directions (where the wind blows from)
+
+wind-n = С
+wind-nne = ССВ
+wind-ne = СВ
+wind-ene = ВСВ
+wind-e = В
+wind-ese = ВЮВ
+wind-se = ЮВ
+wind-sse = ЮЮВ
+wind-s = Ю
+wind-ssw = ЮЮЗ
+wind-sw = ЮЗ
+wind-wsw = ЗЮЗ
+wind-w = З
+wind-wnw = ЗСЗ
+wind-nw = СЗ
+wind-nnw = ССЗ
+
+## Errors of the weather API
+
+error-request-url = Некорректный URL запроса '{ $url }' ({ $error })
+error-request = Ошибка получения данных с сервера. Проверьте подключение к сети и корректность запроса { $request } ({ $error })
+error-response = Сервер вернул ошибку на запрос { $request } ({ $error })
+error-json = Ошибка получения JSON с сервера ({ $error })
+
+## Errors of the config
+
+error-appid = Некорректный ключ API OpenWeatherMap: нужно 32 шестнадцатеричных символа
+error-no-location = Местоположение не выбрано, а его автоопределение отключено
+error-refresh-interval = Интервал обновления погоды должен быть от { $min } до { $max } минут
+error-config-read = Ошибка чтения конфига ({ $error }). Проверьте его наличие и доступ к нему
+error-config-parse =
+    Ошибка парсинга конфига. Проверьте его корректность.
+
+    { $error }
+error-config-serialize =
+    Ошибка сериализации конфига. Проверьте корректность данных.
+
+    { $error }
+error-config-write = Ошибка записи конфига ({ $error }). Проверьте доступ к нему в ФС.
+
+## Time and units
+
+time-just-now = только что
+time-minutes-ago = { $minutes } мин назад
+time-hours-ago = { $hours } ч назад
+time-days-ago = { $days } дн назад
+time-days-duration = { $days } дн { $time }
+unit-meters-per-second = м/с
+unit-mph = миль/ч
+
+## Weather pages
+
+loading = Загружаем инф-цию...
+weather-hourly-placeholder = Тут должен быть почасовой прогноз
+current-feels-like = Ощущается как { $temp }
+current-gust = порывы до { $speed }
+current-min-max = Мин / макс
+current-pressure = Давление
+current-humidity = Влажность
+current-visibility = Видимость
+current-visibility-max = более 10 км
+current-clouds = Облачность
+current-wind = Ветер
+current-sunrise = Восход
+current-sunset = Закат
+current-observed = Наблюдение
+unit-hpa = { $pressure } гПа
+unit-mmhg = { $pressure } мм рт. ст.
+unit-km = { $distance } км
+
+## Daily forecast
+
+daily-loading = Загружаем прогноз...
+daily-today = Сегодня
+daily-tomorrow = Завтра
+weekday-mon = Понедельник
+weekday-tue = Вторник
+weekday-wed = Среда
+weekday-thu = Четверг
+weekday-fri = Пятница
+weekday-sat = Суббота
+weekday-sun = Воскресенье
+daily-precipitation = Осадки { $pop }%
+daily-precipitation-volume = Осадки { $pop }%, { $volume } мм
+
+## Widgets
+
+connectivity-full = Интернет
+connectivity-limited = Нет Интернета
+connectivity-portal = Нужен вход
+connectivity-none = Нет сети
+connectivity-unknown = Сеть ?
+keyboard-backspace = Стереть
+keyboard-space = Пробел
+
+## Main screen
+
+refresh = Обновить
+refreshing = Обновляем...
+weather-updated = Обновлено { $ago }
+weather-no-data = Нет данных о погоде
+weather-attribution = Информация предоставлена OpenWeatherMap
+uptime = Время работы: { $time }
+nav-location = Местоположение
+nav-forecast = Прогноз
+nav-network = Сеть
+nav-system = Система
+nav-back = Назад
+action-retry = Повторить
+action-settings = Настройки
+error-unknown-location = Неизвестное местоположение
+provisioning-done = Устройство подключено к сети, настройки сохранены
+provisioning-no-city = Устройство подключено к сети и ключ API сохранён, но местоположение — нет: { $error }. Укажите его в настройках
+
+## Modal windows
+
+close = Закрыть
+cancel = Отмена
+none = нет
+error-title = Ошибка
+location-search-placeholder = Город или почтовый индекс
+location-search = Найти
+location-not-found = Ничего не найдено
+provisioning-title = Настройка
+provisioning-connect = Нет подключения к сети. Для настройки подключитесь с телефона к Wi-Fi сети:
+provisioning-password = Пароль: { $password }
+provisioning-open-page = и откройте в браузере страницу
+provisioning-connecting = Подключение к сети «{ $ssid }»...
+power-title = Питание
+power-off = Выключить
+power-reboot = Перезагрузить
+power-exit = Выйти в консоль
+power-off-confirm = Выключить устройство?
+power-reboot-confirm = Перезагрузить устройство?
+power-exit-confirm = Закрыть программу и выйти в консоль?
+power-denied = Нет прав (polkit)
+power-unsupported = Не поддерживается
+about-unknown = неизвестно
+about-device = Устройство
+about-os = ОС
+about-kernel = Ядро
+about-addresses = IP-адреса
+about-weather = Погода
+about-cities = Города
+about-icons = Иконки
+about-build = Сборка
+about-build-info = { $commit } от { $date }
+about-license = Лицензия
+about-sources = Источники данных
+
+## Settings
+
+settings-saved = Настройки сохранены
+settings-save = Сохранить
+settings-reset = Сбросить
+settings-done = Готово
+settings-edit = Изменить
+settings-auto = Авто
+settings-units = Единицы
+settings-location-auto = Автоопределение
+settings-location-manual = Вручную
+settings-location-none = не выбрано
+settings-location-select = Выбрать
+settings-appid = Ключ API
+settings-appid-title = Ключ API OpenWeatherMap
+settings-geoip = Основной сервис IP
+settings-refresh-interval = Обновление погоды
+settings-minutes = { $minutes } мин
+settings-hours = { $hours } ч
+settings-theme = Тема
+settings-theme-light = Светлая
+settings-theme-dark = Тёмная
+settings-time-format = Формат времени
+settings-time-24h = 24 часа
+settings-time-12h = 12 часов
+settings-language = Язык
+
+## System monitor
+
+system-loading = Собираем информацию о системе...
+system-cores = Ядер: { $cores }
+system-memory = ОЗУ
+system-memory-details = { $used } / { $total }, подкачка { $swap }
+system-temperature = Температура
+system-throttled = Частота CPU снижена
+system-disk = Диск
+system-disk-details = { $mount }: свободно { $available } из { $total }
+system-uptime = Время работы системы: { $time }
+system-cpu-freq = Частота CPU: { $freq } МГц
+system-under-voltage = Недостаточное напряжение питания!
+system-under-voltage-occurred = Было недостаточное напряжение питания
+unit-kib = { $size } КиБ
+unit-mib = { $size } МиБ
+unit-gib = { $size } ГиБ
+unit-tib = { $size } ТиБ
+
+## Network
+
+network-connecting = Подключение к { $ssid }...
+network-connected = Подключено к { $ssid }
+network-wrong-password = Неверный пароль
+network-timeout = Не удалось подключиться к { $ssid } за отведённое время
+network-failed = Ошибка подключения к { $ssid } (код { $reason })
+network-gateway = Шлюз: { $gateway }
+network-disconnect = Отключиться
+network-no-connection = Нет подключения
+network-available = Доступные
+network-saved = Сохранённые
+network-scanning = Поиск...
+network-hidden = Скрытая сеть
+network-hidden-short = скрытая сеть
+network-in-use = подключено
+network-no-saved = Нет сохранённых сетей
+network-autoconnect-on = Авто: вкл
+network-autoconnect-off = Авто: выкл
+network-password = Пароль
+network-new-password = Новый пароль: { $id }
+network-connect = Подключиться
+network-connect-saved = Подключить
+network-forget = Забыть
+network-ssid = Имя сети
+unit-ghz = { $frequency } ГГц
+
+## Power menu errors
+
+error-power-denied-poweroff = Нет прав на выключение. Разрешите это действие пользователю программы в правилах polkit
+error-power-denied-reboot = Нет прав на перезагрузку. Разрешите это действие пользователю программы в правилах polkit
+error-power-denied-exit = Нет прав на выход в консоль. Разрешите это действие пользователю программы в правилах polkit
+error-power-poweroff = Ошибка запроса на выключение ({ $error })
+error-power-reboot = Ошибка запроса на перезагрузку ({ $error })
+error-power-exit = Ошибка запроса на выход в консоль ({ $error })
+
+## Location
+
+location-source-manual = вручную
+location-source-cache = кэш
+error-location-disabled = Местоположение не задано, а его автоопределение отключено в конфиге
+error-location =
+    Не удалось определить местоположение:
+    { $errors }
+error-location-no-coords = координаты не заданы
+error-location-cache-outdated = кэш устарел
+
+## IP geolocation
+
+error-geoip-ipinfo = Некорректные координаты в ответе ipinfo.io: { $loc }
+error-geoip-ipapi = ipapi.co не вернул координаты ({ $reason })
+error-geoip-db = Ошибка чтения базы GeoIP { $path } ({ $error })
+error-geoip-not-found = Адрес { $ip } не найден в базе GeoIP ({ $error })
+error-geoip-no-location = Нет координат адреса { $ip } в базе GeoIP
+error-geoip-no-latitude = Нет широты в базе GeoIP
+error-geoip-no-longitude = Нет долготы в базе GeoIP
+error-geoip-no-ip = Публичный IP-адрес неизвестен: укажите `ip` или `ip_url` провайдера MaxMind в конфигурации
+error-geoip-no-providers = Не задан ни один сервис определения местоположения по IP
+
+## GPS
+
+error-gps-timeout = GPS-приёмник не определил местоположение за { $timeout } с
+error-gpsd = Ошибка подключения к gpsd ({ $addr }): { $error }
+error-gpsd-closed = gpsd закрыл соединение, не передав местоположение
+
+## Wi-Fi
+
+error-wifi-no-adapter = Wi-Fi адаптер не найден
+error-wifi-security = Этот тип защиты Wi-Fi сети не поддерживается
+error-wifi-no-password = Введите пароль от Wi-Fi сети
+error-wifi-password-length = Пароль от Wi-Fi сети должен занимать от 8 до 63 байт (латинская буква — 1 байт, кириллическая — 2) или состоять из 64 шестнадцатеричных цифр
+error-wifi-password-short = Пароль от Wi-Fi сети должен содержать не менее 8 символов
+error-wifi-no-ssid = Введите имя Wi-Fi сети
+error-wifi-open = Wi-Fi сеть не защищена паролем
+error-wifi-priority = Приоритет должен быть от { $min } до { $max }
+error-hotspot = Не удалось запустить точку доступа ({ $status })
+
+## System information
+
+error-proc-stat-line = Некорректная строка /proc/stat: { $line }
+error-proc-stat = Нет данных о загрузке CPU в /proc/stat
+error-proc-meminfo = Нет данных о памяти в /proc/meminfo
+error-proc-uptime = Некорректные данные в /proc/uptime: { $uptime }
+error-disk = Ошибка получения свободного места на { $mount } ({ $error })
+error-addresses = Ошибка получения IP-адресов ({ $error })
+error-read = Ошибка чтения { $path } ({ $error })
+
+## Cities
+
+error-cities = Ошибка чтения базы городов ({ $error })
+
+## Setup page
+
+setup-title = Настройка Ice
+setup-ssid = Имя Wi-Fi сети
+setup-password = Пароль
+setup-city = Город
+setup-appid = Ключ OpenWeatherMap API
+setup-save = Сохранить
+setup-done-title = Настройки получены
+setup-done = Ice подключается к сети «{ $ssid }». Точка доступа будет отключена.
+setup-wrong-password = Неверный пароль от сети «{ $ssid }»
+setup-timeout = Сеть «{ $ssid }» не ответила за отведённое время
+setup-failed = Ошибка подключения к сети «{ $ssid }» (код { $reason })
+error-city-not-found = Город «{ $city }» не найден
+error-request-closed = Соединение закрыто до конца запроса
+error-request-too-large = Слишком большой запрос
+error-setup-page = Ошибка запуска страницы настройки (порт { $port }): { $error }
@@ -4,28 +4,127 @@ pub mod current;
 pub mod daily;
 pub mod geocoding;
 
-use std::fmt::Display;
+use std::{fmt::Display, sync::OnceLock, time::Duration};
 
 use anyhow::{Result, anyhow};
-use chrono::{DateTime, Local, Timelike};
+use chrono::{DateTime, TimeZone, Timelike};
+use reqwest::Url;
 use serde::{Deserialize, Serialize};
 
-/// Format url into GET/POST-request with given items
-pub fn format_url<U, K, V>(url: U, items: impl Into<Vec<(K, V)>>) -> String
-where
-    U: ToString + Display,
-    K: Display,
-    V: Display,
-{
-    let mut url = format!("{url}");
-    let items = items.into(); // convert items into Vec<(K, V)>
-
-    for item in items {
-        url = format!("{url}{}={}&", item.0, item.1);
+use crate::{i18n::tr, units::Units};
+
+/// Placeholder which is printed instead of the API key
+const REDACTED: &str = "***";
+
+/// Timeout of the connection to the server
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Timeout of the whole request including the response body
+pub const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// HTTP client shared by all requests. Unlike `reqwest::get` it has the
+/// timeouts, so a stalled connection can't hang the request forever
+fn client() -> &'static reqwest::Client {
+    static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+    CLIENT.get_or_init(|| {
+        reqwest::Client::builder()
+            .connect_timeout(CONNECT_TIMEOUT)
+            .timeout(REQUEST_TIMEOUT)
+            .build()
+            .unwrap_or_default()
+    })
+}
+
+/// GET-request to the API with percent-encoded query parameters
+///
+/// ```ignore
+/// let req = Request::new("https://api.openweathermap.org/geo/1.0/direct")
+///     .param("q", "Нижний Новгород")
+///     .param("limit", 5)
+///     .appid("YOUR API KEY");
+/// ```
+///
+/// The `appid` value (and other secret parameters) is never printed:
+/// `Display`, `Debug` and [`Request::redacted`] replace it with `***`, so
+/// the request can be safely written to logs and error messages.
+#[derive(Clone, PartialEq)]
+pub struct Request {
+    base_url: String,
+    params: Vec<(String, String)>,
+    secrets: Vec<String>,
+}
+
+impl Request {
+    /// Creates a new request to the `base_url` endpoint (without `?` and
+    /// query parameters)
+    pub fn new<U: ToString>(base_url: U) -> Self {
+        Self {
+            base_url: base_url.to_string(),
+            params: vec![],
+            secrets: vec![],
+        }
+    }
+
+    /// Adds a query parameter. The value will be percent-encoded
+    pub fn param<K: ToString, V: ToString>(mut self, key: K, value: V) -> Self {
+        self.params.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Adds the parameter which is hidden in the printed request (API key,
+    /// token, etc.)
+    pub fn secret<K: ToString, V: ToString>(mut self, key: K, value: V) -> Self {
+        self.secrets.push(key.to_string());
+        self.param(key, value)
+    }
+
+    /// Adds the `appid` (API key) parameter
+    pub fn appid<A: ToString>(self, appid: A) -> Self {
+        self.secret("appid", appid)
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Builds the full URL of the request
+    pub fn url(&self) -> Result<Url> {
+        Url::parse_with_params(&self.base_url, &self.params).map_err(|err| {
+            anyhow!(tr!(
+                "error-request-url",
+                url = self.base_url.as_str(),
+                error = err.to_string()
+            ))
+        })
     }
-    url.pop(); // remove last `&` symbol
 
-    url
+    /// Builds the full URL of the request with the hidden API key
+    pub fn redacted(&self) -> String {
+        let params = self.params.iter().map(|(key, value)| {
+            if self.secrets.contains(key) {
+                (key.as_str(), REDACTED)
+            } else {
+                (key.as_str(), value.as_str())
+            }
+        });
+
+        match Url::parse_with_params(&self.base_url, params) {
+            Ok(url) => url.to_string(),
+            Err(_) => self.base_url.clone(),
+        }
+    }
+}
+
+impl Display for Request {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.redacted())
+    }
+}
+
+impl std::fmt::Debug for Request {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Request").field(&self.redacted()).finish()
+    }
 }
 
 pub fn floor(num: f32) -> i32 {
@@ -50,7 +149,11 @@ impl Location {
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Main {
     pub feels_like: f32,
+    /// Atmospheric pressure (in hPa)
     pub pressure: i32,
+    /// Humidity (in percent)
+    #[serde(default)]
+    pub humidity: u8,
     pub temp: f32,
     pub temp_max: f32,
     pub temp_min: f32,
@@ -63,72 +166,73 @@ pub struct WeatherMeta {
 }
 
 impl WeatherMeta {
-    pub fn get_descr(&self) -> &str {
-        match self.id {
+    pub fn get_descr(&self) -> String {
+        let id = match self.id {
             // Group 2xx: Thunderstorm
-            200 => "гроза с небольшим дождём",
-            201 => "гроза с дождём",
-            202 => "гроза с сильным дождём",
-            210 => "небольшая гроза",
-            211 => "гроза",
-            212 => "сильная гроза",
-            221 => "рваная гроза",
-            230 => "гроза с лёгким моросящим дождём",
-            231 => "гроза с моросящим дождём",
-            232 => "гроза с сильным моросящим дождём",
+            200 => "weather-thunderstorm-light-rain",
+            201 => "weather-thunderstorm-rain",
+            202 => "weather-thunderstorm-heavy-rain",
+            210 => "weather-light-thunderstorm",
+            211 => "weather-thunderstorm",
+            212 => "weather-heavy-thunderstorm",
+            221 => "weather-ragged-thunderstorm",
+            230 => "weather-thunderstorm-light-drizzle",
+            231 => "weather-thunderstorm-drizzle",
+            232 => "weather-thunderstorm-heavy-drizzle",
 
             // Group 3xx: Drizzle
-            300 | 310 => "небольшой моросящий дождь",
-            301 | 311 | 321 => "моросящий дождь",
-            302 | 312 => "сильный моросящий дождь",
-            313 => "дождь и морось",
-            314 => "сильный моросящий ливень",
+            300 | 310 => "weather-light-drizzle",
+            301 | 311 | 321 => "weather-drizzle",
+            302 | 312 => "weather-heavy-drizzle",
+            313 => "weather-shower-rain-drizzle",
+            314 => "weather-heavy-shower-drizzle",
 
             // Group 5xx: Rain
-            500 => "дождь",
-            501 => "умеренный дождь",
-            502 => "сильный дождь",
-            503 => "очень сильный дождь",
-            504 | 522 => "сильный ливень",
-            511 => "ледяной дождь",
-            520 => "небольшой ливень",
-            521 => "ливень",
-            531 => "неровный дождь",
+            500 => "weather-light-rain",
+            501 => "weather-moderate-rain",
+            502 => "weather-heavy-rain",
+            503 => "weather-very-heavy-rain",
+            504 | 522 => "weather-heavy-shower-rain",
+            511 => "weather-freezing-rain",
+            520 => "weather-light-shower-rain",
+            521 => "weather-shower-rain",
+            531 => "weather-ragged-shower-rain",
 
             // Group 6xx: Snow
-            600 => "небольшой снег",
-            601 => "снег",
-            602 => "сильный снег",
-            611 => "дождь со снегом",
-            612 => "лёгкий дождь со снегом",
-            613..623 => "снег с дождём",
+            600 => "weather-light-snow",
+            601 => "weather-snow",
+            602 => "weather-heavy-snow",
+            611 => "weather-sleet",
+            612 => "weather-light-sleet",
+            613..623 => "weather-rain-and-snow",
 
             // Group 7xx: Atmosphere
-            701 | 741 => "туман",
-            711 => "дым",
-            721 => "дымка",
-            731 => "вихри песка/пыли",
-            751 => "песок",
-            761 => "пыль",
-            762 => "вулканический пепел",
-            771 => "шторм",
-            781 => "торнадо",
+            701 | 741 => "weather-fog",
+            711 => "weather-smoke",
+            721 => "weather-haze",
+            731 => "weather-dust-whirls",
+            751 => "weather-sand",
+            761 => "weather-dust",
+            762 => "weather-volcanic-ash",
+            771 => "weather-squalls",
+            781 => "weather-tornado",
 
             // Group 800: Clear
-            800 => "ясно",
+            800 => "weather-clear-sky",
 
             // Group 80x: Clouds
-            801 => "небольшая облачность",
-            802 => "рассеянные облака",
-            803 => "средняя облачность",
-            804 => "пасмурно",
+            801 => "weather-few-clouds",
+            802 => "weather-scattered-clouds",
+            803 => "weather-broken-clouds",
+            804 => "weather-overcast",
 
             // Other
-            _ => "погода неизвестна",
-        }
+            _ => "weather-unknown",
+        };
+        tr!(id)
     }
 
-    pub fn get_icon(&self, time: DateTime<Local>) -> &str {
+    pub fn get_icon<Tz: TimeZone>(&self, time: DateTime<Tz>) -> &str {
         let time = Time::get(time);
         match self.id {
             200..=232 => match time {
@@ -169,11 +273,11 @@ enum Time {
 }
 
 impl Time {
-    fn get(time: DateTime<Local>) -> Self {
+    fn get<Tz: TimeZone>(time: DateTime<Tz>) -> Self {
         let h = time.hour();
-        if h >= 9 && h < 20 {
+        if (9..20).contains(&h) {
             Self::Day
-        } else if h < 9 && h > 5 {
+        } else if (6..9).contains(&h) {
             Self::Evening
         } else {
             Self::Night
@@ -202,39 +306,89 @@ pub enum WeatherDescription {
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Wind {
+    /// Direction the wind blows from (in degrees, 0 is north)
     pub deg: u16,
     pub gust: Option<f32>,
     pub speed: f32,
 }
 
+impl Wind {
+    /// Returns the number of the compass point (0-15 clockwise from the
+    /// north) the wind blows from
+    pub fn point(&self) -> usize {
+        // Each point covers 22.5° centered on its direction
+        ((f32::from(self.deg % 360) + 11.25) / 22.5) as usize % 16
+    }
+
+    /// Returns the direction the wind blows from as one of 16 compass
+    /// points (`NNW`)
+    pub fn direction(&self) -> String {
+        const POINTS: [&str; 16] = [
+            "n", "nne", "ne", "ene", "e", "ese", "se", "sse", "s", "ssw", "sw", "wsw", "w", "wnw",
+            "nw", "nnw",
+        ];
+        tr!(&format!("wind-{}", POINTS[self.point()]))
+    }
+}
+
+/// Converts the wind `speed` in the `units` to the Beaufort scale (0-12)
+pub fn beaufort(speed: f32, units: Units) -> u8 {
+    /// Lower bounds of the forces 1-12 (in m/s)
+    const LIMITS: [f32; 12] = [
+        0.5, 1.6, 3.4, 5.5, 8.0, 10.8, 13.9, 17.2, 20.8, 24.5, 28.5, 32.7,
+    ];
+    let speed = match units {
+        Units::Metric => speed,
+        Units::Imperial => speed * 0.447_04, // mph -> m/s
+    };
+    LIMITS.iter().take_while(|limit| speed >= **limit).count() as u8
+}
+
+/// Cloudiness
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Clouds {
+    /// In percent
+    pub all: u8,
+}
+
 /// Execution of GET requests to API
 pub trait API {
     /// Function for making a GET requests to OpenWeatherMap API
     ///
-    /// `url`: the base URL of the request, `url_items`: additional
-    /// parameters of the request. For example, `url` =
-    /// `https://www.a.ru/penis?`, and `url_items`:
-    /// `[("a", "A"), ("b", "B")]`, this will be expanded into the URL
-    /// `https://www.a.ru/penis?a=A&b=B`.
+    /// All query parameters are percent-encoded by [`Request`]. For
+    /// example, `Request::new("https://www.a.ru/geo").param("q", "St. John's")`
+    /// will be expanded into the URL `https://www.a.ru/geo?q=St.+John%27s`.
     ///
-    /// > **Note** that you must explicitly specify the
-    /// > `("appid", "YOUR API KEY")` pair in `url_items`.
-    fn get_request<'a, U, I>(
-        url: U,
-        url_items: I,
-    ) -> impl std::future::Future<Output = Result<Self>> + Send
+    /// > **Note** that you must explicitly specify the API key with
+    /// > [`Request::appid`]. It is hidden in all error messages.
+    fn get_request(request: Request) -> impl std::future::Future<Output = Result<Self>> + Send
     where
-        U: ToString + Display + Send,
-        I: Into<Vec<(String, String)>> + Send,
         for<'de> Self: Deserialize<'de>,
     {
-        async {
-            let query = reqwest::get(format_url(url, url_items))
-            .await
-            .map_err(|err| anyhow!("Ошибка получения данных с сервера. Проверьте подключение к сети и корректность запроса ({err})"))?
-            .json::<Self>()
-            .await
-            .map_err(|err| anyhow!("Ошибка получения JSON с сервера ({err})"))?;
+        async move {
+            let url = request.url()?;
+            let query = client()
+                .get(url)
+                .send()
+                .await
+                .map_err(|err| {
+                    anyhow!(tr!(
+                        "error-request",
+                        request = request.to_string(),
+                        error = err.without_url().to_string()
+                    ))
+                })?
+                .error_for_status()
+                .map_err(|err| {
+                    anyhow!(tr!(
+                        "error-response",
+                        request = request.to_string(),
+                        error = err.without_url().to_string()
+                    ))
+                })?
+                .json::<Self>()
+                .await
+                .map_err(|err| anyhow!(tr!("error-json", error = err.without_url().to_string())))?;
 
             Ok(query)
         }
@@ -242,15 +396,135 @@ pub trait API {
 }
 
 #[cfg(test)]
-mod test {
+pub(crate) mod test {
     use super::*;
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    /// Starts a local stand-in server which answers to one HTTP request
+    /// with the given JSON and returns its URL
+    pub(crate) async fn serve_json(body: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = [0; 4096];
+            let _ = stream.read(&mut buf).await.unwrap();
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                body.len()
+            );
+            stream.write_all(response.as_bytes()).await.unwrap();
+        });
+
+        format!("http://{addr}/")
+    }
+
+    #[tokio::test]
+    async fn local_server_test() {
+        let url = serve_json(
+            r#"{"name":"Dzerzhinsk","coord":{"lat":56.24,"lon":43.46},
+            "main":{"feels_like":1.0,"pressure":1000,"humidity":80,"temp":2.0,"temp_max":3.0,"temp_min":1.0},
+            "visibility":10000,"clouds":{"all":75},
+            "sys":{"country":"RU","sunrise":1700000000,"sunset":0},"dt":0,"timezone":10800,
+            "weather":[{"id":800,"main":"Clear"}],"wind":{"deg":0,"speed":1.0}}"#,
+        )
+        .await;
+        let current = current::Current::get(
+            &url,
+            "key",
+            Location {
+                lat: 56.24,
+                lon: 43.46,
+            },
+            crate::units::Units::Metric,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(current.name, "Dzerzhinsk");
+        assert_eq!(current.main.humidity, 80);
+        assert_eq!(current.visibility, Some(10000));
+        assert_eq!(current.clouds.as_ref().map(|clouds| clouds.all), Some(75));
+        assert_eq!(current.sunrise().to_rfc3339(), "2023-11-15T01:13:20+03:00");
+    }
+
+    #[test]
+    fn wind_direction_test() {
+        let wind = |deg| Wind {
+            deg,
+            gust: None,
+            speed: 1.,
+        };
+        assert_eq!(wind(0).point(), 0);
+        assert_eq!(wind(350).point(), 0); // N
+        assert_eq!(wind(45).point(), 2); // NE
+        assert_eq!(wind(200).point(), 9); // SSW
+        assert_eq!(wind(315).point(), 14); // NW
+        assert_eq!(wind(360).point(), 0);
+    }
+
+    #[test]
+    fn beaufort_test() {
+        assert_eq!(beaufort(0., Units::Metric), 0);
+        assert_eq!(beaufort(3.4, Units::Metric), 3);
+        assert_eq!(beaufort(17.1, Units::Metric), 7);
+        assert_eq!(beaufort(17.2, Units::Metric), 8);
+        assert_eq!(beaufort(40., Units::Metric), 12);
+        assert_eq!(beaufort(10., Units::Imperial), 3); // 4.5 m/s
+    }
+
+    #[test]
+    fn request_url_test() {
+        let req = Request::new("https://example.com/weather")
+            .param("aa", "bb")
+            .param("aaa", "bbb");
+
+        assert_eq!(
+            req.url().unwrap().as_str(),
+            "https://example.com/weather?aa=bb&aaa=bbb"
+        );
+    }
+
+    #[test]
+    fn request_encoding_test() {
+        let req = Request::new("https://example.com/geo")
+            .param("q", "St. John's, CA")
+            .param("city", "Нижний Новгород");
+        let url = req.url().unwrap();
+
+        assert_eq!(
+            url.as_str(),
+            "https://example.com/geo?q=St.+John%27s%2C+CA&city=%D0%9D%D0%B8%D0%B6%D0%BD%D0%B8%D0%B9+%D0%9D%D0%BE%D0%B2%D0%B3%D0%BE%D1%80%D0%BE%D0%B4"
+        );
+
+        let pairs = url.query_pairs().collect::<Vec<_>>();
+        assert_eq!(pairs[1].1, "Нижний Новгород");
+    }
+
+    #[test]
+    fn request_redact_test() {
+        let req = Request::new("https://example.com/weather")
+            .param("lat", 56.3)
+            .appid("secret_key");
+
+        assert_eq!(
+            req.to_string(),
+            "https://example.com/weather?lat=56.3&appid=***"
+        );
+        assert!(req.url().unwrap().as_str().contains("appid=secret_key"));
+        assert!(!format!("{req:?}").contains("secret_key"));
+        assert!(!format!("{req:#?}").contains("secret_key"));
+    }
 
     #[test]
-    fn format_url_test() {
-        let url = "https://pornhub.com?";
-        let items = vec![("aa", "bb"), ("aaa", "bbb")];
-        let fmt_url = format_url(&url, items);
+    fn request_bad_url_test() {
+        let req = Request::new("not an url").appid("secret_key");
+        let err = req.url().unwrap_err().to_string();
 
-        assert_eq!(fmt_url, "https://pornhub.com?aa=bb&aaa=bbb");
+        assert!(!err.contains("secret_key"));
     }
 }
@@ -4,11 +4,11 @@ use anyhow::Result;
 use chrono::{DateTime, FixedOffset};
 use serde::{Deserialize, Serialize};
 
-use super::{API, Location, Main, WeatherMeta, Wind};
+use super::{API, Clouds, Location, Main, Request, WeatherMeta, Wind};
 use crate::units::Units;
 
-/// The URL that is used to make GET requests to the API
-pub const API_URL: &str = "https://api.openweathermap.org/data/2.5/weather?";
+/// The default URL that is used to make GET requests to the API
+pub const API_URL: &str = "https://api.openweathermap.org/data/2.5/weather";
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Current {
@@ -21,29 +21,49 @@ pub struct Current {
     pub timezone: i32,
     pub weather: Vec<WeatherMeta>,
     pub wind: Wind,
+
+    /// Visibility (in meters, max 10 km)
+    pub visibility: Option<u32>,
+    pub clouds: Option<Clouds>,
 }
 
 impl API for Current {}
 
 impl Current {
-    pub async fn get<A: ToString>(appid: A, loc: Location, units: Units) -> Result<Self> {
+    pub async fn get<A: ToString>(
+        url: &str,
+        appid: A,
+        loc: Location,
+        units: Units,
+    ) -> Result<Self> {
         Self::get_request(
-            API_URL,
-            [
-                ("appid".to_string(), appid.to_string()),
-                ("units".to_string(), units.to_string()),
-                ("lat".to_string(), loc.lat.to_string()),
-                ("lon".to_string(), loc.lon.to_string()),
-            ],
+            Request::new(url)
+                .appid(appid)
+                .param("units", units)
+                .param("lat", loc.lat)
+                .param("lon", loc.lon),
         )
         .await
     }
 
-    pub fn get_time(&self) -> DateTime<FixedOffset> {
-        let time = DateTime::from_timestamp(self.dt, 0)
+    /// Converts the UNIX `timestamp` to the time in the location timezone
+    fn local_time(&self, timestamp: i64) -> DateTime<FixedOffset> {
+        DateTime::from_timestamp(timestamp, 0)
             .unwrap_or_default()
-            .with_timezone(&FixedOffset::east_opt(self.timezone).unwrap());
-        time
+            .with_timezone(&FixedOffset::east_opt(self.timezone).unwrap())
+    }
+
+    /// Returns the time of the observation
+    pub fn get_time(&self) -> DateTime<FixedOffset> {
+        self.local_time(self.dt)
+    }
+
+    pub fn sunrise(&self) -> DateTime<FixedOffset> {
+        self.local_time(self.sys.sunrise)
+    }
+
+    pub fn sunset(&self) -> DateTime<FixedOffset> {
+        self.local_time(self.sys.sunset)
     }
 }
 
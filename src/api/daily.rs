@@ -1,14 +1,14 @@
 //! 5 day weather forecast
 
 use anyhow::Result;
-use chrono::{DateTime, FixedOffset};
+use chrono::{DateTime, FixedOffset, NaiveDate, Timelike};
 use serde::{Deserialize, Serialize};
 
-use super::{API, Location, Main, WeatherMeta, Wind};
+use super::{API, Location, Main, Request, WeatherMeta, Wind};
 use crate::units::Units;
 
-/// The URL that is used to make GET requests to the API
-pub const API_URL: &str = "https://api.openweathermap.org/data/2.5/forecast?";
+/// The default URL that is used to make GET requests to the API
+pub const API_URL: &str = "https://api.openweathermap.org/data/2.5/forecast";
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Daily {
@@ -20,15 +20,13 @@ pub struct Daily {
 impl API for Daily {}
 
 impl Daily {
-    pub async fn get(appid: &str, loc: Location, units: Units) -> Result<Self> {
+    pub async fn get(url: &str, appid: &str, loc: Location, units: Units) -> Result<Self> {
         Self::get_request(
-            API_URL,
-            [
-                ("appid".to_string(), appid.to_string()),
-                ("units".to_string(), units.to_string()),
-                ("lat".to_string(), loc.lat.to_string()),
-                ("lon".to_string(), loc.lon.to_string()),
-            ],
+            Request::new(url)
+                .appid(appid)
+                .param("units", units)
+                .param("lat", loc.lat)
+                .param("lon", loc.lon),
         )
         .await
     }
@@ -37,11 +35,78 @@ impl Daily {
     ///
     /// `idx` - number of the measured torque (index in the array)
     pub fn get_time(&self, idx: usize) -> DateTime<FixedOffset> {
-        let time = DateTime::from_timestamp(self.list[idx].dt, 0)
+        DateTime::from_timestamp(self.list[idx].dt, 0)
             .unwrap_or_default()
-            .with_timezone(&FixedOffset::east_opt(self.city.timezone).unwrap());
-        time
+            .with_timezone(&FixedOffset::east_opt(self.city.timezone).unwrap())
     }
+
+    /// Groups the 3-hour measurements by the days in the location timezone
+    pub fn days(&self) -> Vec<Day> {
+        let mut days: Vec<Day> = vec![];
+
+        for (idx, data) in self.list.iter().enumerate() {
+            let time = self.get_time(idx);
+            let date = time.date_naive();
+
+            let day = match days.last_mut() {
+                Some(day) if day.date == date => day,
+                _ => {
+                    days.push(Day {
+                        date,
+                        entries: vec![],
+                        temp_min: f32::MAX,
+                        temp_max: f32::MIN,
+                        pop: 0.,
+                        precipitation: 0.,
+                        wind: data.wind.clone(),
+                        main_entry: idx,
+                    });
+                    days.last_mut().unwrap()
+                }
+            };
+
+            day.entries.push(idx);
+            day.temp_min = day.temp_min.min(data.main.temp_min);
+            day.temp_max = day.temp_max.max(data.main.temp_max);
+            day.pop = day.pop.max(data.pop);
+            day.precipitation += data.precipitation();
+            if data.wind.speed > day.wind.speed {
+                day.wind = data.wind.clone();
+            }
+            // The weather of the day is the one closest to the noon
+            let noon_dist = |idx| (self.get_time(idx).hour() as i32 - 12).abs();
+            if noon_dist(idx) < noon_dist(day.main_entry) {
+                day.main_entry = idx;
+            }
+        }
+        days
+    }
+}
+
+/// Weather of the one day aggregated from the 3-hour measurements
+#[derive(Debug, Clone)]
+pub struct Day {
+    /// Date in the location timezone
+    pub date: NaiveDate,
+
+    /// Indexes of the measurements in [`Daily::list`]
+    pub entries: Vec<usize>,
+
+    pub temp_min: f32,
+    pub temp_max: f32,
+
+    /// Max probability of precipitation (0.0 - 1.0)
+    pub pop: f32,
+
+    /// Total rain and snow volume (in mm)
+    pub precipitation: f32,
+
+    /// The strongest wind of the day
+    pub wind: Wind,
+
+    /// Index of the measurement which represents the day (the closest one
+    /// to the noon)
+    pub main_entry: usize,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -50,6 +115,31 @@ pub struct WeatherData {
     pub main: Main,
     pub weather: Vec<WeatherMeta>,
     pub wind: Wind,
+
+    /// Probability of precipitation (0.0 - 1.0)
+    #[serde(default)]
+    pub pop: f32,
+    pub rain: Option<Volume>,
+    pub snow: Option<Volume>,
+}
+
+impl WeatherData {
+    /// Rain and snow volume for the 3 hours (in mm)
+    pub fn precipitation(&self) -> f32 {
+        [&self.rain, &self.snow]
+            .into_iter()
+            .flatten()
+            .map(|volume| volume.h3)
+            .sum()
+    }
+}
+
+/// Precipitation volume
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct Volume {
+    /// For the last 3 hours (in mm)
+    #[serde(rename = "3h", default)]
+    pub h3: f32,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -62,3 +152,49 @@ pub struct City {
     pub sunrise: i64,
     pub sunset: i64,
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn days_test() {
+        // 21:00 UTC is the next day in UTC+3
+        let entry = |dt: i64, temp: f32, rain: Option<f32>, speed: f32| {
+            serde_json::json!({
+                "dt": dt,
+                "main": {"feels_like": temp, "pressure": 1000, "temp": temp,
+                         "temp_max": temp, "temp_min": temp},
+                "weather": [{"id": 800, "main": "Clear"}],
+                "wind": {"deg": 90, "speed": speed},
+                "pop": rain.map_or(0., |_| 0.6),
+                "rain": rain.map(|mm| serde_json::json!({"3h": mm})),
+            })
+        };
+        let day = 1_700_006_400; // 2023-11-15 00:00 UTC
+        let daily: Daily = serde_json::from_value(serde_json::json!({
+            "cnt": 4,
+            "list": [
+                entry(day + 9 * 3600, 5., Some(1.5), 3.),
+                entry(day + 18 * 3600, 2., Some(0.5), 7.),
+                entry(day + 21 * 3600, 0., None, 2.),
+                entry(day + 24 * 3600, -1., None, 1.),
+            ],
+            "city": {"id": 0, "name": "Dzerzhinsk", "coord": {"lat": 56.24, "lon": 43.46},
+                     "country": "RU", "timezone": 10800, "sunrise": 0, "sunset": 0},
+        }))
+        .unwrap();
+
+        let days = daily.days();
+        assert_eq!(days.len(), 2);
+        assert_eq!(days[0].date, NaiveDate::from_ymd_opt(2023, 11, 15).unwrap());
+        assert_eq!(days[0].entries, [0, 1]);
+        assert_eq!((days[0].temp_min, days[0].temp_max), (2., 5.));
+        assert_eq!(days[0].precipitation, 2.);
+        assert_eq!(days[0].pop, 0.6);
+        assert_eq!(days[0].wind.speed, 7.);
+        assert_eq!(days[0].main_entry, 0); // 12:00 local
+        assert_eq!(days[1].entries, [2, 3]);
+        assert_eq!(days[1].precipitation, 0.);
+    }
+}
@@ -1,15 +1,21 @@
-//! Get coordinates of given location
+//! Get coordinates of given location (and name of given coordinates)
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
 
-use super::API;
+use super::{API, Request};
 
-/// The URL that is used to make GET requests to the API
-pub const API_URL: &str = "http://api.openweathermap.org/geo/1.0/direct?";
+/// The default URL that is used to make GET requests to the API
+pub const API_URL: &str = "https://api.openweathermap.org/geo/1.0/direct";
+
+/// The default URL of the reverse geocoding API (coordinates -> name)
+pub const REVERSE_API_URL: &str = "https://api.openweathermap.org/geo/1.0/reverse";
+
+/// The default URL of the postal code geocoding API
+pub const ZIP_API_URL: &str = "https://api.openweathermap.org/geo/1.0/zip";
 
 /// Max items in the API response
-pub const LIMIT: &str = "5";
+pub const LIMIT: u8 = 5;
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Location(pub Vec<LocationInfo>);
@@ -17,17 +23,64 @@ pub struct Location(pub Vec<LocationInfo>);
 impl API for Location {}
 
 impl Location {
-    pub async fn get(location: &str, appid: &str) -> Result<Self> {
+    pub async fn get(url: &str, location: &str, appid: &str) -> Result<Self> {
         Location::get_request(
-            API_URL,
-            [
-                ("q".to_string(), location.to_string()),
-                ("limit".to_string(), LIMIT.to_string()),
-                ("appid".to_string(), appid.to_string()),
-            ],
+            Request::new(url)
+                .param("q", location)
+                .param("limit", LIMIT)
+                .appid(appid),
         )
         .await
     }
+
+    /// Gets names of locations near the given coordinates
+    pub async fn get_reverse(url: &str, loc: super::Location, appid: &str) -> Result<Self> {
+        Location::get_request(
+            Request::new(url)
+                .param("lat", loc.lat)
+                .param("lon", loc.lon)
+                .param("limit", LIMIT)
+                .appid(appid),
+        )
+        .await
+    }
+}
+
+/// Response of the postal code geocoding API
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ZipInfo {
+    pub zip: String,
+    pub name: String,
+    pub country: String,
+    pub lat: f32,
+    pub lon: f32,
+}
+
+impl API for ZipInfo {}
+
+impl ZipInfo {
+    /// Gets location by the postal code. `country` is the ISO 3166 country
+    /// code, the API uses `US` if it isn't specified
+    pub async fn get(url: &str, zip: &str, country: Option<&str>, appid: &str) -> Result<Self> {
+        let zip = match country {
+            Some(country) => format!("{zip},{country}"),
+            None => zip.to_string(),
+        };
+        ZipInfo::get_request(Request::new(url).param("zip", zip).appid(appid)).await
+    }
+}
+
+impl From<ZipInfo> for LocationInfo {
+    fn from(zip: ZipInfo) -> Self {
+        Self {
+            name: zip.name,
+            country: zip.country,
+            state: None,
+            local_names: None,
+            lat: zip.lat,
+            lon: zip.lon,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -40,23 +93,19 @@ pub struct LocationInfo {
     pub lon: f32,
 }
 
-impl ToString for LocationInfo {
-    fn to_string(&self) -> String {
+impl std::fmt::Display for LocationInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         if let Some(state) = &self.state {
-            format!("{} ({}, {})", &self.name, &state, &self.country)
+            write!(f, "{} ({}, {})", &self.name, &state, &self.country)
         } else {
-            format!("{} ({})", &self.name, &self.country)
+            write!(f, "{} ({})", &self.name, &self.country)
         }
     }
 }
 
 impl PartialEq for LocationInfo {
     fn eq(&self, other: &Self) -> bool {
-        &self.name == &other.name && &self.country == &other.country && &self.state == &other.state
-    }
-
-    fn ne(&self, other: &Self) -> bool {
-        &self.name != &other.name || &self.country != &other.country || &self.state != &other.state
+        self.name == other.name && self.country == other.country && self.state == other.state
     }
 }
 
@@ -69,3 +118,42 @@ pub struct LocalNames {
     pub en: Option<String>,
     pub ru: Option<String>,
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::api::test::serve_json;
+
+    #[tokio::test]
+    async fn reverse_test() {
+        let url = serve_json(
+            r#"[{"name":"Dzerzhinsk","local_names":{"ru":"Дзержинск"},
+            "lat":56.2389,"lon":43.4631,"country":"RU","state":"Nizhny Novgorod Oblast"}]"#,
+        )
+        .await;
+        let loc = super::super::Location {
+            lat: 56.24,
+            lon: 43.46,
+        };
+        let found = Location::get_reverse(&url, loc, "key").await.unwrap();
+
+        assert_eq!(
+            found.0[0].to_string(),
+            "Dzerzhinsk (Nizhny Novgorod Oblast, RU)"
+        );
+    }
+
+    #[tokio::test]
+    async fn zip_test() {
+        let url = serve_json(
+            r#"{"zip":"606000","name":"Dzerzhinsk","lat":56.2389,"lon":43.4631,"country":"RU"}"#,
+        )
+        .await;
+        let found = ZipInfo::get(&url, "606000", Some("RU"), "key")
+            .await
+            .unwrap();
+        let found = LocationInfo::from(found);
+
+        assert_eq!(found.to_string(), "Dzerzhinsk (RU)");
+    }
+}
@@ -1,11 +1,16 @@
 //! Main application code
 
+pub mod cities;
+pub mod geoip;
+pub mod gps;
 pub mod location;
 pub mod network;
+pub mod power;
+pub mod provision;
 pub mod system;
 
 /* TODO:
  * - [-] network
- * - [ ] system
+ * - [X] system
  * - [X] location
  */
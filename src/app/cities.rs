@@ -0,0 +1,242 @@
+//! Offline geocoding from the bundled city database
+//!
+//! The database is a GeoNames dump (for example, `cities15000.txt`) in the
+//! original tab-separated format. It is optional: if the file is missing,
+//! offline geocoding is simply unavailable.
+
+use anyhow::{Result, anyhow};
+use std::{fs, path::Path};
+use tokio::sync::OnceCell;
+
+use crate::{api::geocoding::LocationInfo, consts::CITIES_DB_PATH, i18n::tr};
+
+/// Mean radius of the Earth in kilometres
+const EARTH_RADIUS: f32 = 6371.;
+
+#[derive(Debug, Clone)]
+struct City {
+    name: String,
+    country: String,
+    lat: f32,
+    lon: f32,
+    population: u64,
+}
+
+impl City {
+    /// Parses one line of the GeoNames dump
+    ///
+    /// Columns: `geonameid`, `name`, `asciiname`, `alternatenames`,
+    /// `latitude`, `longitude`, `feature class`, `feature code`,
+    /// `country code`, `cc2`, `admin1-4 code`, `population`, ...
+    fn parse(line: &str) -> Option<(Self, Vec<&str>)> {
+        let cols = line.split('\t').collect::<Vec<_>>();
+        if cols.len() < 15 {
+            return None;
+        }
+
+        let city = Self {
+            name: cols[1].to_string(),
+            country: cols[8].to_string(),
+            lat: cols[4].parse().ok()?,
+            lon: cols[5].parse().ok()?,
+            population: cols[14].parse().unwrap_or(0),
+        };
+
+        // Alternate names in the other scripts are most of the dump, they
+        // are skipped to save the memory of the device
+        let mut names = vec![cols[1], cols[2]];
+        names.extend(
+            cols[3]
+                .split(',')
+                .filter(|name| !name.is_empty() && is_latin_or_cyrillic(name)),
+        );
+
+        Some((city, names))
+    }
+
+    fn distance(&self, lat: f32, lon: f32) -> f32 {
+        let (lat1, lat2) = (self.lat.to_radians(), lat.to_radians());
+        let dlat = lat2 - lat1;
+        let dlon = (lon - self.lon).to_radians();
+
+        let a = (dlat / 2.).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.).sin().powi(2);
+        2. * EARTH_RADIUS * a.sqrt().asin()
+    }
+}
+
+/// Checks that the letters of the name are Latin or Cyrillic, including
+/// the diacritics (`Köln`, `Niš`, `Қарағанды`). Names in CJK, Arabic and
+/// the other scripts aren't searched by the users of the program
+fn is_latin_or_cyrillic(name: &str) -> bool {
+    name.chars().all(|c| {
+        !c.is_alphabetic()
+            || matches!(
+                c,
+                // Latin, Latin-1 Supplement and Latin Extended
+                '\u{0}'..='\u{24f}'
+                    // Combining diacritical marks
+                    | '\u{300}'..='\u{36f}'
+                    // Cyrillic and Cyrillic Supplement
+                    | '\u{400}'..='\u{52f}'
+                    // Latin Extended Additional
+                    | '\u{1e00}'..='\u{1eff}'
+            )
+    })
+}
+
+impl From<&City> for LocationInfo {
+    fn from(city: &City) -> Self {
+        Self {
+            name: city.name.clone(),
+            country: city.country.clone(),
+            state: None,
+            local_names: None,
+            lat: city.lat,
+            lon: city.lon,
+        }
+    }
+}
+
+/// City database with the prefix search and the nearest-city lookup
+#[derive(Debug, Default)]
+pub struct CityDb {
+    cities: Vec<City>,
+
+    /// Lowercase names (including alternate names) of the cities with
+    /// indexes in `cities`, sorted for the binary search
+    index: Vec<(String, usize)>,
+}
+
+impl CityDb {
+    /// Returns the database from [`CITIES_DB_PATH`]. It is read only once
+    /// (in the blocking thread pool, the dump is large), `None` means that
+    /// the database is not installed
+    pub async fn bundled() -> Option<&'static Self> {
+        static DB: OnceCell<Option<CityDb>> = OnceCell::const_new();
+        DB.get_or_init(|| async {
+            tokio::task::spawn_blocking(|| Self::open(CITIES_DB_PATH).ok())
+                .await
+                .ok()
+                .flatten()
+        })
+        .await
+        .as_ref()
+    }
+
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let contents = fs::read_to_string(&path)
+            .map_err(|err| anyhow!(tr!("error-cities", error = err.to_string())))?;
+        Ok(Self::parse(&contents))
+    }
+
+    /// Parses the GeoNames dump. Malformed lines are skipped
+    pub fn parse(contents: &str) -> Self {
+        let mut db = Self::default();
+
+        for (city, names) in contents.lines().filter_map(City::parse) {
+            let idx = db.cities.len();
+            let mut names = names
+                .into_iter()
+                .map(|name| name.to_lowercase())
+                .collect::<Vec<_>>();
+            names.sort();
+            names.dedup();
+
+            db.index.extend(names.into_iter().map(|name| (name, idx)));
+            db.cities.push(city);
+        }
+        db.index.sort();
+
+        db
+    }
+
+    pub fn len(&self) -> usize {
+        self.cities.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cities.is_empty()
+    }
+
+    /// Finds at most `limit` cities whose name starts with `prefix`. The
+    /// most populated cities go first
+    pub fn search(&self, prefix: &str, limit: usize) -> Vec<LocationInfo> {
+        let prefix = prefix.trim().to_lowercase();
+        if prefix.is_empty() {
+            return vec![];
+        }
+
+        let start = self
+            .index
+            .partition_point(|(name, _)| name.as_str() < prefix.as_str());
+        let mut found = self.index[start..]
+            .iter()
+            .take_while(|(name, _)| name.starts_with(&prefix))
+            .map(|(_, idx)| *idx)
+            .collect::<Vec<_>>();
+        found.sort();
+        found.dedup();
+        found.sort_by(|a, b| self.cities[*b].population.cmp(&self.cities[*a].population));
+
+        found
+            .into_iter()
+            .take(limit)
+            .map(|idx| LocationInfo::from(&self.cities[idx]))
+            .collect()
+    }
+
+    /// Finds the nearest city to the given coordinates
+    pub fn nearest(&self, lat: f32, lon: f32) -> Option<LocationInfo> {
+        self.cities
+            .iter()
+            .min_by(|a, b| a.distance(lat, lon).total_cmp(&b.distance(lat, lon)))
+            .map(LocationInfo::from)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const DUMP: &str = "520555\tNizhniy Novgorod\tNizhniy Novgorod\tNizhny Novgorod,Нижний Новгород,下诺夫哥罗德\t56.32867\t44.00205\tP\tPPLA\tRU\t\t51\t\t\t\t1284164\t\t144\tEurope/Moscow\t2022-01-01
+566532\tDzerzhinsk\tDzerzhinsk\tДзержинск\t56.24143\t43.45539\tP\tPPL\tRU\t\t51\t\t\t\t240762\t\t100\tEurope/Moscow\t2022-01-01
+2886242\tCologne\tCologne\tKoeln,Köln,Кёльн,ケルン\t50.93333\t6.95\tP\tPPLA3\tDE\t\t07\t\t\t\t963395\t\t59\tEurope/Berlin\t2022-01-01
+625144\tMinsk\tMinsk\tМинск\t53.9\t27.56667\tP\tPPLC\tBY\t\t05\t\t\t\t1742124\t\t220\tEurope/Minsk\t2022-01-01
+bad line";
+
+    #[test]
+    fn parse_test() {
+        let db = CityDb::parse(DUMP);
+        assert_eq!(db.len(), 4);
+    }
+
+    #[test]
+    fn search_test() {
+        let db = CityDb::parse(DUMP);
+
+        let found = db.search("нижний", 5);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].name, "Nizhniy Novgorod");
+        assert_eq!(found[0].country, "RU");
+
+        let found = db.search("M", 5);
+        assert_eq!(found[0].name, "Minsk");
+
+        assert!(db.search("London", 5).is_empty());
+        assert!(db.search("下诺", 5).is_empty());
+
+        // Alternate names with diacritics are searched too
+        assert_eq!(db.search("Köln", 5)[0].name, "Cologne");
+        assert_eq!(db.search("кёльн", 5)[0].name, "Cologne");
+        assert!(db.search("ケルン", 5).is_empty());
+        assert!(db.search("  ", 5).is_empty());
+    }
+
+    #[test]
+    fn nearest_test() {
+        let db = CityDb::parse(DUMP);
+        let city = db.nearest(56.2, 43.5).unwrap();
+
+        assert_eq!(city.name, "Dzerzhinsk");
+    }
+}
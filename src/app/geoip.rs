@@ -0,0 +1,407 @@
+//! IP geolocation providers
+//!
+//! Every provider implements the [`GeoIp`] trait. [`locate`] tries the
+//! configured providers in order and returns the first answer, so a
+//! rate-limited or unreachable service is skipped.
+
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    future::Future,
+    net::IpAddr,
+    path::PathBuf,
+    sync::{Arc, LazyLock, Mutex},
+};
+
+use super::location::Location;
+use crate::{
+    api::{API, Request},
+    i18n::tr,
+};
+
+/// Default URL of the ip-api.com (free endpoint supports plain HTTP only)
+pub const IP_API_URL: &str = "http://ip-api.com/json/";
+
+/// Default URL of the ipinfo.io
+pub const IPINFO_URL: &str = "https://ipinfo.io/json";
+
+/// Default URL of the ipapi.co
+pub const IPAPI_CO_URL: &str = "https://ipapi.co/json/";
+
+/// Default path to the GeoIP2/GeoLite2 City database
+pub const MAXMIND_DB_PATH: &str = "/usr/share/GeoIP/GeoLite2-City.mmdb";
+
+/// Detection of the location by IP address
+pub trait GeoIp {
+    fn locate(&self) -> impl Future<Output = Result<Location>> + Send;
+}
+
+/// ip-api.com
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct IpApi {
+    pub url: String,
+}
+
+impl GeoIp for IpApi {
+    async fn locate(&self) -> Result<Location> {
+        /// Fields requested from the ip-api.com
+        const FIELDS: &str = "country,city,regionName,lat,lon";
+
+        Location::get_request(Request::new(&self.url).param("fields", FIELDS)).await
+    }
+}
+
+/// ipinfo.io. Works without `token` with the limited number of requests
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct IpInfo {
+    pub url: String,
+    pub token: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct IpInfoResponse {
+    city: Option<String>,
+    region: Option<String>,
+    country: Option<String>,
+
+    /// `"lat,lon"`
+    loc: String,
+}
+
+impl API for IpInfoResponse {}
+
+impl GeoIp for IpInfo {
+    async fn locate(&self) -> Result<Location> {
+        let mut req = Request::new(&self.url);
+        if let Some(token) = &self.token {
+            req = req.secret("token", token);
+        }
+        let resp = IpInfoResponse::get_request(req).await?;
+
+        let (lat, lon) = resp
+            .loc
+            .split_once(',')
+            .and_then(|(lat, lon)| Some((lat.trim().parse().ok()?, lon.trim().parse().ok()?)))
+            .ok_or_else(|| anyhow!(tr!("error-geoip-ipinfo", loc = resp.loc.as_str())))?;
+
+        Ok(Location {
+            city: resp.city,
+            country: resp.country,
+            region: resp.region,
+            lat,
+            lon,
+            accuracy: None,
+        })
+    }
+}
+
+/// ipapi.co
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct IpApiCo {
+    pub url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct IpApiCoResponse {
+    city: Option<String>,
+    region: Option<String>,
+    country_name: Option<String>,
+    latitude: Option<f32>,
+    longitude: Option<f32>,
+
+    /// Is set if the request is rejected (e.g. rate limit)
+    reason: Option<String>,
+}
+
+impl API for IpApiCoResponse {}
+
+impl GeoIp for IpApiCo {
+    async fn locate(&self) -> Result<Location> {
+        let resp = IpApiCoResponse::get_request(Request::new(&self.url)).await?;
+        match (resp.latitude, resp.longitude) {
+            (Some(lat), Some(lon)) => Ok(Location {
+                city: resp.city,
+                country: resp.country_name,
+                region: resp.region,
+                lat,
+                lon,
+                accuracy: None,
+            }),
+            _ => Err(anyhow!(tr!(
+                "error-geoip-ipapi",
+                reason = resp.reason.unwrap_or_default()
+            ))),
+        }
+    }
+}
+
+/// Local GeoIP2/GeoLite2 City database (MaxMind DB format)
+///
+/// The device is usually behind NAT, and its LAN address is never in the
+/// database, so the public address must be known. With the static `ip`
+/// the provider works offline. Otherwise the address is requested from
+/// the optional `ip_url`, which is the only outbound request
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct MaxMind {
+    #[serde(default = "MaxMind::default_path")]
+    pub path: PathBuf,
+    pub ip: Option<IpAddr>,
+
+    /// Service which returns the public IP address (`{"ip": "..."}`)
+    pub ip_url: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PublicIp {
+    ip: IpAddr,
+}
+
+impl API for PublicIp {}
+
+impl MaxMind {
+    fn default_path() -> PathBuf {
+        PathBuf::from(MAXMIND_DB_PATH)
+    }
+
+    /// Returns the public IP address of the device: the static one or
+    /// the answer of `ip_url`
+    async fn public_ip(&self) -> Result<IpAddr> {
+        if let Some(ip) = self.ip {
+            return Ok(ip);
+        }
+        let Some(url) = &self.ip_url else {
+            return Err(anyhow!(tr!("error-geoip-no-ip")));
+        };
+        let resp = PublicIp::get_request(Request::new(url).param("format", "json")).await?;
+        Ok(resp.ip)
+    }
+
+    /// Returns the database from `path`. It is read only once; a missing
+    /// database isn't cached, so it's found after the installation
+    fn reader(&self) -> Result<Arc<Reader>> {
+        static READERS: LazyLock<Mutex<HashMap<PathBuf, Arc<Reader>>>> =
+            LazyLock::new(Default::default);
+
+        let mut readers = READERS.lock().unwrap_or_else(|err| err.into_inner());
+        if let Some(reader) = readers.get(&self.path) {
+            return Ok(reader.clone());
+        }
+        let reader = Reader::open_readfile(&self.path).map_err(|err| {
+            anyhow!(tr!(
+                "error-geoip-db",
+                path = self.path.display().to_string(),
+                error = err.to_string()
+            ))
+        })?;
+        let reader = Arc::new(reader);
+        readers.insert(self.path.clone(), reader.clone());
+        Ok(reader)
+    }
+
+    fn lookup(&self, ip: IpAddr) -> Result<Location> {
+        use maxminddb::geoip2;
+
+        let reader = self.reader()?;
+        let city: geoip2::City = reader.lookup(ip).map_err(|err| {
+            anyhow!(tr!(
+                "error-geoip-not-found",
+                ip = ip.to_string(),
+                error = err.to_string()
+            ))
+        })?;
+
+        let name = |names: Option<&std::collections::BTreeMap<&str, &str>>| {
+            let names = names?;
+            names
+                .get("en")
+                .or_else(|| names.values().next())
+                .map(|name| name.to_string())
+        };
+        let loc = city
+            .location
+            .as_ref()
+            .ok_or_else(|| anyhow!(tr!("error-geoip-no-location", ip = ip.to_string())))?;
+
+        Ok(Location {
+            city: name(city.city.as_ref().and_then(|c| c.names.as_ref())),
+            country: name(city.country.as_ref().and_then(|c| c.names.as_ref())),
+            region: name(
+                city.subdivisions
+                    .as_ref()
+                    .and_then(|s| s.first())
+                    .and_then(|s| s.names.as_ref()),
+            ),
+            lat: loc
+                .latitude
+                .ok_or_else(|| anyhow!(tr!("error-geoip-no-latitude")))? as f32,
+            lon: loc
+                .longitude
+                .ok_or_else(|| anyhow!(tr!("error-geoip-no-longitude")))? as f32,
+            accuracy: loc.accuracy_radius.map(|km| f32::from(km) * 1000.),
+        })
+    }
+}
+
+impl GeoIp for MaxMind {
+    async fn locate(&self) -> Result<Location> {
+        let ip = self.public_ip().await?;
+        let this = self.clone();
+        tokio::task::spawn_blocking(move || this.lookup(ip)).await?
+    }
+}
+
+/// Database read into the memory
+type Reader = maxminddb::Reader<Vec<u8>>;
+
+/// IP geolocation provider from the config
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(tag = "provider", rename_all = "snake_case")]
+pub enum Provider {
+    IpApi(IpApi),
+    #[serde(rename = "ipinfo")]
+    IpInfo(IpInfo),
+    #[serde(rename = "ipapi_co")]
+    IpApiCo(IpApiCo),
+    #[serde(rename = "maxmind")]
+    MaxMind(MaxMind),
+}
+
+impl Provider {
+    pub fn name(&self) -> &str {
+        match self {
+            Self::IpApi(_) => "ip-api.com",
+            Self::IpInfo(_) => "ipinfo.io",
+            Self::IpApiCo(_) => "ipapi.co",
+            Self::MaxMind(_) => "MaxMind DB",
+        }
+    }
+
+    /// Default providers. Only the HTTPS services are used, the free
+    /// ip-api.com endpoint is plain HTTP and must be enabled explicitly
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self::IpApiCo(IpApiCo {
+                url: IPAPI_CO_URL.to_string(),
+            }),
+            Self::IpInfo(IpInfo {
+                url: IPINFO_URL.to_string(),
+                token: None,
+            }),
+        ]
+    }
+}
+
+impl GeoIp for Provider {
+    async fn locate(&self) -> Result<Location> {
+        match self {
+            Self::IpApi(p) => p.locate().await,
+            Self::IpInfo(p) => p.locate().await,
+            Self::IpApiCo(p) => p.locate().await,
+            Self::MaxMind(p) => p.locate().await,
+        }
+    }
+}
+
+/// Tries `providers` in order and returns the first detected location
+pub async fn locate(providers: &[Provider]) -> Result<Location> {
+    let mut errors = vec![];
+    for provider in providers {
+        match provider.locate().await {
+            Ok(loc) => return Ok(loc),
+            Err(why) => errors.push(format!("{}: {why}", provider.name())),
+        }
+    }
+
+    if errors.is_empty() {
+        Err(anyhow!(tr!("error-geoip-no-providers")))
+    } else {
+        Err(anyhow!("{}", errors.join("; ")))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::api::test::serve_json;
+
+    #[tokio::test]
+    async fn ipinfo_test() {
+        let url = serve_json(
+            r#"{"ip":"192.0.2.1","city":"Dzerzhinsk","region":"Nizhny Novgorod Oblast","country":"RU","loc":"56.2414,43.4554"}"#,
+        )
+        .await;
+        let loc = IpInfo { url, token: None }.locate().await.unwrap();
+
+        assert_eq!(loc.city.as_deref(), Some("Dzerzhinsk"));
+        assert_eq!((loc.lat, loc.lon), (56.2414, 43.4554));
+    }
+
+    #[tokio::test]
+    async fn failover_test() {
+        let url = serve_json(
+            r#"{"city":"Dzerzhinsk","region":"Nizhny Novgorod Oblast","country_name":"Russia","latitude":56.24,"longitude":43.46}"#,
+        )
+        .await;
+        let providers = [
+            Provider::MaxMind(MaxMind {
+                path: "/nonexistent.mmdb".into(),
+                ip: Some("192.0.2.1".parse().unwrap()),
+                ip_url: None,
+            }),
+            Provider::IpApiCo(IpApiCo { url }),
+        ];
+        let loc = locate(&providers).await.unwrap();
+
+        assert_eq!(loc.country.as_deref(), Some("Russia"));
+        assert!(locate(&providers[..1]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn public_ip_test() {
+        let ip_url = serve_json(r#"{"ip":"203.0.113.7"}"#).await;
+        let mut maxmind = MaxMind {
+            path: "/nonexistent.mmdb".into(),
+            ip: None,
+            ip_url: Some(ip_url),
+        };
+
+        assert_eq!(
+            maxmind.public_ip().await.unwrap(),
+            "203.0.113.7".parse::<IpAddr>().unwrap()
+        );
+
+        // No outbound requests unless `ip_url` is set
+        maxmind.ip_url = None;
+        assert_eq!(
+            maxmind.public_ip().await.unwrap_err().to_string(),
+            tr!("error-geoip-no-ip")
+        );
+    }
+
+    #[test]
+    fn config_test() {
+        #[derive(Deserialize)]
+        struct Conf {
+            geoip: Vec<Provider>,
+        }
+
+        let conf: Conf = toml::from_str(
+            r#"
+            [[geoip]]
+            provider = "maxmind"
+            path = "/var/lib/GeoIP/offices.mmdb"
+
+            [[geoip]]
+            provider = "ipinfo"
+            url = "https://ipinfo.io/json"
+            token = "secret"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(conf.geoip.len(), 2);
+        assert_eq!(conf.geoip[0].name(), "MaxMind DB");
+        assert!(matches!(&conf.geoip[0], Provider::MaxMind(maxmind) if maxmind.ip_url.is_none()));
+    }
+}
@@ -0,0 +1,180 @@
+//! Get location from the GPS receiver via `gpsd`
+//!
+//! `gpsd` speaks the JSON protocol over TCP (port 2947 by default): the
+//! client sends `?WATCH={"enable":true,"json":true};` and the daemon
+//! answers with newline-separated JSON objects. Position reports have the
+//! `TPV` class.
+
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::{
+    io::{AsyncBufReadExt, AsyncWriteExt, BufReader},
+    net::TcpStream,
+    time,
+};
+
+use crate::i18n::tr;
+
+/// Default address of the `gpsd`
+pub const GPSD_ADDR: &str = "127.0.0.1:2947";
+
+/// Default time (in seconds) to wait for the fix
+pub const FIX_TIMEOUT: u64 = 10;
+
+const WATCH: &[u8] = b"?WATCH={\"enable\":true,\"json\":true};\n";
+
+/// Mode of the GPS fix
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(from = "u8")]
+pub enum Mode {
+    NoFix,
+    Fix2D,
+    Fix3D,
+}
+
+impl From<u8> for Mode {
+    fn from(mode: u8) -> Self {
+        match mode {
+            2 => Self::Fix2D,
+            3 => Self::Fix3D,
+            _ => Self::NoFix,
+        }
+    }
+}
+
+/// `TPV` (time-position-velocity) report of the `gpsd`
+#[derive(Debug, Deserialize)]
+struct Tpv {
+    class: String,
+    mode: Option<Mode>,
+    time: Option<String>,
+    lat: Option<f32>,
+    lon: Option<f32>,
+    #[serde(rename = "altMSL", alias = "alt")]
+    alt: Option<f32>,
+    eph: Option<f32>,
+    epx: Option<f32>,
+    epy: Option<f32>,
+}
+
+/// GPS fix
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Fix {
+    pub lat: f32,
+    pub lon: f32,
+    pub alt: Option<f32>,
+
+    /// Estimated horizontal error (in metres)
+    pub accuracy: Option<f32>,
+    pub mode: Mode,
+
+    /// UTC time of the fix (ISO 8601)
+    pub time: Option<String>,
+}
+
+impl Fix {
+    fn from_tpv(tpv: Tpv) -> Option<Self> {
+        let mode = tpv.mode.unwrap_or(Mode::NoFix);
+        if tpv.class != "TPV" || mode == Mode::NoFix {
+            return None;
+        }
+
+        let accuracy = tpv.eph.or(match (tpv.epx, tpv.epy) {
+            (Some(x), Some(y)) => Some(x.max(y)),
+            _ => None,
+        });
+
+        Some(Self {
+            lat: tpv.lat?,
+            lon: tpv.lon?,
+            alt: if mode == Mode::Fix3D { tpv.alt } else { None },
+            accuracy,
+            mode,
+            time: tpv.time,
+        })
+    }
+
+    /// Waits for the first 2D or 3D fix from the `gpsd` at `addr`
+    pub async fn get(addr: &str, timeout: Duration) -> Result<Self> {
+        time::timeout(timeout, Self::watch(addr))
+            .await
+            .map_err(|_| anyhow!(tr!("error-gps-timeout", timeout = timeout.as_secs())))?
+    }
+
+    async fn watch(addr: &str) -> Result<Self> {
+        let mut stream = TcpStream::connect(addr)
+            .await
+            .map_err(|err| anyhow!(tr!("error-gpsd", addr = addr, error = err.to_string())))?;
+        stream.write_all(WATCH).await?;
+
+        let mut lines = BufReader::new(stream).lines();
+        while let Some(line) = lines.next_line().await? {
+            // Other messages (`VERSION`, `DEVICES`, `SKY`...) are ignored
+            if let Some(fix) = serde_json::from_str::<Tpv>(&line)
+                .ok()
+                .and_then(Self::from_tpv)
+            {
+                return Ok(fix);
+            }
+        }
+
+        Err(anyhow!(tr!("error-gpsd-closed")))
+    }
+}
+
+impl From<&Fix> for crate::api::Location {
+    fn from(fix: &Fix) -> Self {
+        Self {
+            lat: fix.lat,
+            lon: fix.lon,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use tokio::{io::AsyncReadExt, net::TcpListener};
+
+    /// Starts a fake `gpsd` which sends given lines after the `?WATCH`
+    /// command
+    async fn fake_gpsd(lines: &'static [&'static str]) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut buf = [0; 128];
+            let _ = stream.read(&mut buf).await.unwrap();
+            for line in lines {
+                stream.write_all(line.as_bytes()).await.unwrap();
+                stream.write_all(b"\n").await.unwrap();
+            }
+        });
+
+        addr.to_string()
+    }
+
+    #[tokio::test]
+    async fn fix_test() {
+        let addr = fake_gpsd(&[
+            r#"{"class":"VERSION","release":"3.22"}"#,
+            r#"{"class":"TPV","device":"/dev/ttyUSB0","mode":1}"#,
+            r#"{"class":"TPV","device":"/dev/ttyUSB0","mode":3,"time":"2025-05-01T10:00:00.000Z","lat":56.24,"lon":43.46,"altMSL":120.5,"epx":4.5,"epy":6.0}"#,
+        ])
+        .await;
+        let fix = Fix::get(&addr, Duration::from_secs(5)).await.unwrap();
+
+        assert_eq!(fix.mode, Mode::Fix3D);
+        assert_eq!((fix.lat, fix.lon), (56.24, 43.46));
+        assert_eq!(fix.alt, Some(120.5));
+        assert_eq!(fix.accuracy, Some(6.0));
+    }
+
+    #[tokio::test]
+    async fn no_fix_test() {
+        let addr = fake_gpsd(&[r#"{"class":"TPV","mode":1}"#]).await;
+        assert!(Fix::get(&addr, Duration::from_secs(5)).await.is_err());
+    }
+}
@@ -1,11 +1,29 @@
 //! Autodetect user location and get (lat, lon) coordinates
+//!
+//! The location is resolved from an ordered chain of sources: manual
+//! coordinates from the config, GPS, the cached last fix and IP lookup.
 
-use anyhow::Result;
-use reqwest;
-use serde::Deserialize;
+use anyhow::{Result, anyhow};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::Display,
+    fs,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
-const API_URL: &str = "http://ip-api.com/json/?fields=country,city,regionName,lat,lon";
+use crate::{
+    api::API,
+    app::{geoip, gps},
+    config::{self, Config},
+    consts::{CACHE_DIR_PATH, GEO_CACHE},
+    i18n::tr,
+};
 
+/// Max age (in seconds) of the cached location
+pub const CACHE_TTL: i64 = 6 * 60 * 60;
+
+/// Location detected by IP address (see [`geoip`])
 #[derive(Debug, Deserialize, Clone)]
 pub struct Location {
     pub city: Option<String>,
@@ -16,11 +34,263 @@ pub struct Location {
 
     pub lat: f32,
     pub lon: f32,
+
+    /// Estimated error (in metres)
+    pub accuracy: Option<f32>,
+}
+
+impl API for Location {}
+
+/// Source of the user location
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    /// Coordinates from the config file or from the location selector
+    Manual,
+    Gps,
+    Cache,
+    Ip,
+}
+
+impl Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Manual => tr!("location-source-manual"),
+                Self::Gps => "GPS".to_string(),
+                Self::Cache => tr!("location-source-cache"),
+                Self::Ip => "IP".to_string(),
+            }
+        )
+    }
+}
+
+/// User location with its source
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct Resolved {
+    pub source: Source,
+    pub lat: f32,
+    pub lon: f32,
+
+    /// Human-readable name of the location (if known)
+    pub name: Option<String>,
+
+    /// Estimated error (in metres)
+    pub accuracy: Option<f32>,
+
+    /// UNIX time of the location detection
+    pub time: i64,
+}
+
+impl Resolved {
+    pub fn new(source: Source, lat: f32, lon: f32) -> Self {
+        Self {
+            source,
+            lat,
+            lon,
+            name: None,
+            accuracy: None,
+            time: chrono::Utc::now().timestamp(),
+        }
+    }
+
+    pub fn read_cache<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        let mut loc: Self = serde_json::from_str(&contents)?;
+        loc.source = Source::Cache;
+        Ok(loc)
+    }
+
+    pub fn write_cache<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        if let Some(dir) = path.as_ref().parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string(self)?)?;
+        Ok(())
+    }
+
+    /// Returns the age of the location (in seconds)
+    pub fn age(&self) -> i64 {
+        chrono::Utc::now().timestamp() - self.time
+    }
+}
+
+impl From<&Resolved> for crate::api::Location {
+    fn from(loc: &Resolved) -> Self {
+        Self {
+            lat: loc.lat,
+            lon: loc.lon,
+        }
+    }
 }
 
-impl Location {
-    pub async fn get_by_ip() -> Result<Self> {
-        let response = reqwest::get(API_URL).await?.json::<Self>().await?;
-        Ok(response)
+/// Resolves the user location from the sources enabled in the config
+#[derive(Debug, Clone)]
+pub struct Resolver {
+    pub coords: Option<(f32, f32)>,
+    pub autodetect: bool,
+    pub gps: config::Gps,
+    pub geoip: Vec<geoip::Provider>,
+    pub cache: PathBuf,
+
+    /// Don't use the cached location (e.g. the network has changed and the
+    /// cache may contain the old city)
+    pub skip_cache: bool,
+}
+
+impl Resolver {
+    pub fn new(conf: &Config) -> Self {
+        Self {
+            coords: conf.coords,
+            autodetect: conf.autodetect_location,
+            gps: conf.gps.clone(),
+            geoip: conf.geoip.clone(),
+            cache: Path::new(CACHE_DIR_PATH).join(GEO_CACHE),
+            skip_cache: false,
+        }
+    }
+
+    pub fn skip_cache(mut self, skip: bool) -> Self {
+        self.skip_cache = skip;
+        self
+    }
+
+    /// Returns the ordered list of sources which will be tried
+    pub fn sources(&self) -> Vec<Source> {
+        let mut sources = vec![];
+        if self.coords.is_some() {
+            sources.push(Source::Manual);
+        }
+        if self.autodetect {
+            sources.push(Source::Gps);
+            if !self.skip_cache {
+                sources.push(Source::Cache);
+            }
+            sources.push(Source::Ip);
+        }
+        sources
+    }
+
+    /// Tries all sources in order and returns the first detected location.
+    /// Locations from GPS and IP are cached
+    pub async fn resolve(&self) -> Result<Resolved> {
+        let mut errors = vec![];
+
+        for source in self.sources() {
+            match self.get(source).await {
+                Ok(loc) => {
+                    if matches!(source, Source::Gps | Source::Ip) {
+                        // The cache is an optimization only, so its write errors are ignored
+                        let _ = loc.write_cache(&self.cache);
+                    }
+                    return Ok(loc);
+                }
+                Err(why) => errors.push(format!("{source}: {why}")),
+            }
+        }
+
+        if errors.is_empty() {
+            Err(anyhow!(tr!("error-location-disabled")))
+        } else {
+            Err(anyhow!(tr!("error-location", errors = errors.join("\n"))))
+        }
+    }
+
+    async fn get(&self, source: Source) -> Result<Resolved> {
+        match source {
+            Source::Manual => {
+                let (lat, lon) = self
+                    .coords
+                    .ok_or_else(|| anyhow!(tr!("error-location-no-coords")))?;
+                Ok(Resolved::new(Source::Manual, lat, lon))
+            }
+            Source::Gps => {
+                let fix =
+                    gps::Fix::get(&self.gps.addr, Duration::from_secs(self.gps.timeout)).await?;
+                let mut loc = Resolved::new(Source::Gps, fix.lat, fix.lon);
+                loc.accuracy = fix.accuracy;
+                Ok(loc)
+            }
+            Source::Cache => {
+                let loc = Resolved::read_cache(&self.cache)?;
+                if loc.age() > CACHE_TTL {
+                    return Err(anyhow!(tr!("error-location-cache-outdated")));
+                }
+                Ok(loc)
+            }
+            Source::Ip => {
+                let ip = geoip::locate(&self.geoip).await?;
+                let mut loc = Resolved::new(Source::Ip, ip.lat, ip.lon);
+                loc.accuracy = ip.accuracy;
+                loc.name = match (ip.city, ip.country) {
+                    (Some(city), Some(country)) => Some(format!("{city} ({country})")),
+                    (city, _) => city,
+                };
+                Ok(loc)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::api::test::serve_json;
+
+    fn resolver(ip_url: String, cache: &str) -> Resolver {
+        let ip = geoip::IpApi { url: ip_url };
+        Resolver {
+            coords: None,
+            autodetect: true,
+            gps: config::Gps {
+                // nobody listens on the port 9 (discard)
+                addr: "127.0.0.1:9".to_string(),
+                timeout: 1,
+            },
+            geoip: vec![geoip::Provider::IpApi(ip)],
+            cache: std::env::temp_dir().join(cache),
+            skip_cache: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn manual_test() {
+        let mut res = resolver("http://127.0.0.1:9/".to_string(), "ice-test-manual.json");
+        res.coords = Some((56.24, 43.46));
+        res.autodetect = false;
+
+        assert_eq!(res.sources(), vec![Source::Manual]);
+        let loc = res.resolve().await.unwrap();
+        assert_eq!(loc.source, Source::Manual);
+        assert_eq!((loc.lat, loc.lon), (56.24, 43.46));
+
+        res.coords = None;
+        assert!(res.resolve().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn chain_test() {
+        let url = serve_json(
+            r#"{"city":"Dzerzhinsk","country":"Russia","regionName":"Nizhny Novgorod Oblast","lat":56.24,"lon":43.46}"#,
+        )
+        .await;
+        let res = resolver(url, "ice-test-chain.json");
+        let _ = fs::remove_file(&res.cache);
+
+        // GPS and cache are unavailable
+        let loc = res.resolve().await.unwrap();
+        assert_eq!(loc.source, Source::Ip);
+        assert_eq!(loc.name.as_deref(), Some("Dzerzhinsk (Russia)"));
+
+        // IP location is cached
+        let loc = res.resolve().await.unwrap();
+        assert_eq!(loc.source, Source::Cache);
+        assert_eq!((loc.lat, loc.lon), (56.24, 43.46));
+
+        let res = res.skip_cache(true);
+        assert_eq!(res.sources(), vec![Source::Gps, Source::Ip]);
+
+        let _ = fs::remove_file(&res.cache);
     }
 }
@@ -1,42 +1,1355 @@
-//! Work with Wi-Fi (scan and connect)
+//! Work with Wi-Fi (scan, connect and manage saved networks)
 
-use anyhow::Result;
-use std::collections::HashMap;
-use zbus::{Connection, zvariant::Value};
+use anyhow::{Result, anyhow};
+use futures_util::{Stream, StreamExt, stream};
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+use zbus::{
+    Connection,
+    zvariant::{ObjectPath, OwnedObjectPath, OwnedValue, Value},
+};
 
-pub async fn scan_wifi() -> Result<Vec<String>> {
+use crate::i18n::tr;
+
+/// `NM_STATE_CONNECTED_LOCAL`: there is only local IPv4 and/or IPv6
+/// connectivity
+pub const NM_STATE_CONNECTED_LOCAL: u32 = 50;
+
+/// `NM_STATE_CONNECTED_GLOBAL`: the system has global network connectivity
+pub const NM_STATE_CONNECTED_GLOBAL: u32 = 70;
+
+/// Connectivity to the Internet checked by NetworkManager
+/// (`NMConnectivityState`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Connectivity {
+    /// The check is disabled or hasn't run yet
+    Unknown,
+    None,
+
+    /// Behind the captive portal (e.g. a hotel Wi-Fi login page)
+    Portal,
+
+    /// Connected to the network, but there is no access to the Internet
+    Limited,
+    Full,
+}
+
+impl From<u32> for Connectivity {
+    fn from(state: u32) -> Self {
+        match state {
+            1 => Self::None,
+            2 => Self::Portal,
+            3 => Self::Limited,
+            4 => Self::Full,
+            _ => Self::Unknown,
+        }
+    }
+}
+
+/// Change of the network state reported by NetworkManager
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NetworkChange {
+    /// New state of the NetworkManager (`NMState`)
+    State(u32),
+
+    /// Object path of the new primary connection (`/` if there is none)
+    PrimaryConnection(String),
+    Connectivity(Connectivity),
+
+    /// Signal strength (in percent) of the active Wi-Fi access point.
+    /// `None` if Wi-Fi isn't connected
+    WifiSignal(Option<u8>),
+}
+
+async fn nm_proxy(connection: &Connection) -> Result<zbus::Proxy<'static>> {
+    let proxy = zbus::Proxy::new(
+        connection,
+        "org.freedesktop.NetworkManager",
+        "/org/freedesktop/NetworkManager",
+        "org.freedesktop.NetworkManager",
+    )
+    .await?;
+    Ok(proxy)
+}
+
+/// Returns the stream of the NetworkManager state, connectivity, primary
+/// connection and Wi-Fi signal changes. The current values are sent first
+pub async fn network_changes_on(
+    connection: &Connection,
+) -> Result<impl Stream<Item = NetworkChange> + use<>> {
+    let proxy = nm_proxy(connection).await?;
+    // Property streams start with the current value, but the state is
+    // reported by the signal only
+    let state: u32 = proxy.call("state", &()).await?;
+
+    let states = proxy
+        .receive_signal("StateChanged")
+        .await?
+        .filter_map(|msg| async move { msg.body().deserialize::<u32>().ok() })
+        .map(NetworkChange::State);
+    let primary = proxy
+        .receive_property_changed::<OwnedObjectPath>("PrimaryConnection")
+        .await
+        .filter_map(|change| async move { change.get().await.ok() })
+        .map(|path| NetworkChange::PrimaryConnection(path.to_string()));
+    let connectivity = proxy
+        .receive_property_changed::<u32>("Connectivity")
+        .await
+        .filter_map(|change| async move { change.get().await.ok() })
+        .map(|state| NetworkChange::Connectivity(state.into()));
+    let signal = wifi_signal_changes(connection)
+        .await?
+        .map(NetworkChange::WifiSignal);
+
+    Ok(
+        stream::once(async move { NetworkChange::State(state) }).chain(stream::select(
+            stream::select(states, primary),
+            stream::select(connectivity, signal),
+        )),
+    )
+}
+
+pub async fn network_changes() -> Result<impl Stream<Item = NetworkChange>> {
     let connection = Connection::system().await?;
+    network_changes_on(&connection).await
+}
+
+/// Event of the Wi-Fi device used to track the signal strength
+enum SignalEvent {
+    /// New active access point (`/` if there is none)
+    Active(String),
+
+    /// New strength of the access point at the path
+    Strength(String, u8),
+}
+
+async fn ap_strength(connection: &Connection, path: &str) -> Option<u8> {
+    if path == "/" {
+        return None;
+    }
+    let path = OwnedObjectPath::try_from(path).ok()?;
+    let ap = device_proxy(
+        connection,
+        &path,
+        "org.freedesktop.NetworkManager.AccessPoint",
+    )
+    .await
+    .ok()?;
+    ap.get_property("Strength").await.ok()
+}
+
+/// Returns the stream of the signal strength of the access point which
+/// the first Wi-Fi device is connected to. The current strength is sent
+/// first (like the current value of the `ActiveAccessPoint` property). The
+/// stream is empty if there is no Wi-Fi device
+async fn wifi_signal_changes(
+    connection: &Connection,
+) -> Result<impl Stream<Item = Option<u8>> + use<>> {
+    let Some(device) = wifi_devices(connection).await?.into_iter().next() else {
+        return Ok(stream::empty().left_stream());
+    };
+    let wireless = zbus::Proxy::new(
+        connection,
+        "org.freedesktop.NetworkManager",
+        device,
+        "org.freedesktop.NetworkManager.Device.Wireless",
+    )
+    .await?;
+    let active = wireless
+        .receive_property_changed::<OwnedObjectPath>("ActiveAccessPoint")
+        .await
+        .filter_map(|change| async move { change.get().await.ok() })
+        .map(|path| SignalEvent::Active(path.to_string()));
+    // Strength of all access points is reported, the active one is picked
+    // out below
+    let strengths = zbus::MessageStream::for_match_rule(
+        zbus::MatchRule::builder()
+            .msg_type(zbus::message::Type::Signal)
+            .interface("org.freedesktop.DBus.Properties")?
+            .member("PropertiesChanged")?
+            .arg(0, "org.freedesktop.NetworkManager.AccessPoint")?
+            .build(),
+        connection,
+        None,
+    )
+    .await?
+    .filter_map(|msg| async move {
+        let msg = msg.ok()?;
+        let path = msg.header().path()?.to_string();
+        let (_, changed, _): (String, HashMap<String, OwnedValue>, Vec<String>) =
+            msg.body().deserialize().ok()?;
+        let strength = u8::try_from(changed.get("Strength")?.try_clone().ok()?).ok()?;
+        Some(SignalEvent::Strength(path, strength))
+    });
+
+    let events = stream::select(active, strengths).boxed();
+    let connection = connection.clone();
+
+    Ok(
+        stream::unfold((events, String::new()), move |(mut events, mut active)| {
+            let connection = connection.clone();
+            async move {
+                loop {
+                    match events.next().await? {
+                        SignalEvent::Active(path) => {
+                            let strength = ap_strength(&connection, &path).await;
+                            active = path;
+                            return Some((strength, (events, active)));
+                        }
+                        SignalEvent::Strength(path, strength) if path == active => {
+                            return Some((Some(strength), (events, active)));
+                        }
+                        SignalEvent::Strength(..) => {}
+                    }
+                }
+            }
+        })
+        .right_stream(),
+    )
+}
+
+/// Waits until NetworkManager establishes any connection (e.g. at boot).
+/// Returns `false` if there is no connection after the `timeout`
+pub async fn wait_connected_on(connection: &Connection, timeout: Duration) -> Result<bool> {
+    let nm = nm_proxy(connection).await?;
+    let start = Instant::now();
+    loop {
+        let state: u32 = nm.call("state", &()).await?;
+        if state >= NM_STATE_CONNECTED_LOCAL {
+            return Ok(true);
+        }
+        if start.elapsed() >= timeout {
+            return Ok(false);
+        }
+        tokio::time::sleep(Duration::from_millis(500)).await;
+    }
+}
+
+pub async fn wait_connected(timeout: Duration) -> Result<bool> {
+    let connection = Connection::system().await?;
+    wait_connected_on(&connection, timeout).await
+}
+
+/// `NM_DEVICE_TYPE_WIFI`
+const NM_DEVICE_TYPE_WIFI: u32 = 2;
+
+/// `NM_802_11_AP_FLAGS_PRIVACY`: encryption is required (WEP if there are no
+/// WPA flags)
+const NM_802_11_AP_FLAGS_PRIVACY: u32 = 0x1;
+
+/// `NM_802_11_AP_SEC_KEY_MGMT_PSK`
+const NM_802_11_AP_SEC_KEY_MGMT_PSK: u32 = 0x100;
+
+/// `NM_802_11_AP_SEC_KEY_MGMT_802_1X`
+const NM_802_11_AP_SEC_KEY_MGMT_802_1X: u32 = 0x200;
+
+/// `NM_802_11_AP_SEC_KEY_MGMT_SAE`
+const NM_802_11_AP_SEC_KEY_MGMT_SAE: u32 = 0x400;
+
+/// Max time to wait for the end of the Wi-Fi scanning
+pub const SCAN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Security of the Wi-Fi network
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Security {
+    Open,
+    Wep,
+
+    /// WPA/WPA2 Personal (also WPA2/WPA3 transition mode)
+    WpaPsk,
+
+    /// WPA3 Personal
+    Sae,
+
+    /// WPA/WPA2/WPA3 Enterprise (802.1X)
+    Enterprise,
+}
+
+impl Security {
+    fn from_flags(flags: u32, wpa_flags: u32, rsn_flags: u32) -> Self {
+        let key_mgmt = wpa_flags | rsn_flags;
+        if key_mgmt & NM_802_11_AP_SEC_KEY_MGMT_802_1X != 0 {
+            Self::Enterprise
+        } else if key_mgmt & NM_802_11_AP_SEC_KEY_MGMT_PSK != 0 {
+            Self::WpaPsk
+        } else if key_mgmt & NM_802_11_AP_SEC_KEY_MGMT_SAE != 0 {
+            Self::Sae
+        } else if flags & NM_802_11_AP_FLAGS_PRIVACY != 0 {
+            Self::Wep
+        } else {
+            Self::Open
+        }
+    }
+
+    /// Converts `key-mgmt` of the connection profile
+    fn from_key_mgmt(key_mgmt: Option<&str>) -> Self {
+        match key_mgmt {
+            None => Self::Open,
+            Some("none" | "ieee8021x") => Self::Wep,
+            Some("sae") => Self::Sae,
+            Some("wpa-eap" | "wpa-eap-suite-b-192") => Self::Enterprise,
+            Some(_) => Self::WpaPsk,
+        }
+    }
+
+    pub fn is_secured(&self) -> bool {
+        *self != Self::Open
+    }
+}
+
+/// Wi-Fi access point found by the scanning
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccessPoint {
+    pub ssid: String,
+
+    /// Signal strength (in percent)
+    pub strength: u8,
+
+    /// Frequency (in MHz)
+    pub frequency: u32,
+    pub security: Security,
+
+    /// The device is connected to this access point
+    pub in_use: bool,
+
+    /// D-Bus object path of the access point
+    pub path: String,
+
+    /// D-Bus object path of the Wi-Fi device which has found this access point
+    pub device: String,
+}
+
+async fn device_proxy<'a>(
+    connection: &Connection,
+    path: &'a OwnedObjectPath,
+    interface: &'static str,
+) -> Result<zbus::Proxy<'a>> {
     let proxy = zbus::Proxy::new(
-        &connection,
+        connection,
         "org.freedesktop.NetworkManager",
-        "unix:path=/org/freedesktop/NetworkManager/Devices/0",
+        path.as_str(),
+        interface,
+    )
+    .await?;
+    Ok(proxy)
+}
+
+/// Returns D-Bus object paths of all Wi-Fi devices
+pub async fn wifi_devices(connection: &Connection) -> Result<Vec<OwnedObjectPath>> {
+    let nm = nm_proxy(connection).await?;
+    let devices: Vec<OwnedObjectPath> = nm.call("GetDevices", &()).await?;
+
+    let mut wifi = vec![];
+    for device in devices {
+        let proxy =
+            device_proxy(connection, &device, "org.freedesktop.NetworkManager.Device").await?;
+        if proxy.get_property::<u32>("DeviceType").await? == NM_DEVICE_TYPE_WIFI {
+            wifi.push(device);
+        }
+    }
+
+    Ok(wifi)
+}
+
+/// Asks the device to scan for access points and waits (at most `timeout`)
+/// for the end of the scanning
+pub async fn request_scan(
+    connection: &Connection,
+    device: &OwnedObjectPath,
+    timeout: Duration,
+) -> Result<()> {
+    let proxy = device_proxy(
+        connection,
+        device,
         "org.freedesktop.NetworkManager.Device.Wireless",
     )
     .await?;
+    let last_scan = proxy.get_property::<i64>("LastScan").await.unwrap_or(0);
 
-    let access_points: Vec<String> = proxy
-        .call_method("GetAllAccessPoints", &())
-        .await?
-        .body()
-        .deserialize()?;
+    let options: HashMap<&str, Value> = HashMap::new();
+    proxy.call_method("RequestScan", &(options)).await?;
+
+    let started = Instant::now();
+    while started.elapsed() < timeout {
+        // Property cache is updated by the `PropertiesChanged` signal
+        if proxy.get_property::<i64>("LastScan").await.unwrap_or(0) != last_scan {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(250)).await;
+    }
+    Ok(())
+}
+
+/// Returns all access points visible to the device
+pub async fn access_points(
+    connection: &Connection,
+    device: &OwnedObjectPath,
+) -> Result<Vec<AccessPoint>> {
+    let proxy = device_proxy(
+        connection,
+        device,
+        "org.freedesktop.NetworkManager.Device.Wireless",
+    )
+    .await?;
+    let paths: Vec<OwnedObjectPath> = proxy.call("GetAllAccessPoints", &()).await?;
+    let active = proxy
+        .get_property::<OwnedObjectPath>("ActiveAccessPoint")
+        .await
+        .ok();
+
+    let mut points = vec![];
+    for path in paths {
+        let ap = device_proxy(
+            connection,
+            &path,
+            "org.freedesktop.NetworkManager.AccessPoint",
+        )
+        .await?;
+        let ssid: Vec<u8> = ap.get_property("Ssid").await?;
+
+        points.push(AccessPoint {
+            ssid: String::from_utf8_lossy(&ssid).to_string(),
+            strength: ap.get_property("Strength").await?,
+            frequency: ap.get_property("Frequency").await?,
+            security: Security::from_flags(
+                ap.get_property("Flags").await?,
+                ap.get_property("WpaFlags").await?,
+                ap.get_property("RsnFlags").await?,
+            ),
+            in_use: active.as_ref() == Some(&path),
+            path: path.to_string(),
+            device: device.to_string(),
+        });
+    }
+
+    Ok(points)
+}
+
+/// Leaves one access point per SSID (the active one or the one with the
+/// best signal) and sorts them: the active network first, then by signal
+/// strength. Hidden networks (with empty SSID) are skipped
+pub fn dedup_by_ssid(points: Vec<AccessPoint>) -> Vec<AccessPoint> {
+    let mut unique: Vec<AccessPoint> = vec![];
+    for ap in points.into_iter().filter(|ap| !ap.ssid.is_empty()) {
+        match unique.iter_mut().find(|known| known.ssid == ap.ssid) {
+            Some(known) => {
+                if (ap.in_use, ap.strength) > (known.in_use, known.strength) {
+                    *known = ap;
+                }
+            }
+            None => unique.push(ap),
+        }
+    }
+    unique.sort_by_key(|ap| std::cmp::Reverse((ap.in_use, ap.strength)));
+    unique
+}
+
+/// Scans for Wi-Fi networks on all wireless devices
+pub async fn scan_wifi_on(connection: &Connection, timeout: Duration) -> Result<Vec<AccessPoint>> {
+    let devices = wifi_devices(connection).await?;
+    if devices.is_empty() {
+        return Err(anyhow!(tr!("error-wifi-no-adapter")));
+    }
+
+    let mut points = vec![];
+    for device in &devices {
+        // NetworkManager refuses to scan too often; the previous results are used then
+        let _ = request_scan(connection, device, timeout).await;
+        points.extend(access_points(connection, device).await?);
+    }
+
+    Ok(dedup_by_ssid(points))
+}
+
+/// Scans for Wi-Fi networks using NetworkManager on the system bus
+pub async fn scan_wifi() -> Result<Vec<AccessPoint>> {
+    let connection = Connection::system().await?;
+    scan_wifi_on(&connection, SCAN_TIMEOUT).await
+}
+
+/// Details of the active network connection
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectionInfo {
+    /// Name of the connection profile (SSID for Wi-Fi networks)
+    pub id: String,
+
+    /// Type of the connection (`802-11-wireless`, `802-3-ethernet`...)
+    pub kind: String,
 
-    Ok(access_points)
+    /// IPv4 address with the prefix length (`192.168.1.10/24`)
+    pub ip: Option<String>,
+    pub gateway: Option<String>,
+
+    /// D-Bus object path of the active connection
+    pub path: String,
 }
 
-pub async fn connect_wifi(ssid: &str, pass: &str) -> Result<()> {
+impl ConnectionInfo {
+    pub fn is_wifi(&self) -> bool {
+        self.kind == "802-11-wireless"
+    }
+}
+
+/// Returns details of the primary connection (`None` if there is no
+/// connection)
+pub async fn connection_info_on(connection: &Connection) -> Result<Option<ConnectionInfo>> {
+    let nm = nm_proxy(connection).await?;
+    let primary: OwnedObjectPath = nm.get_property("PrimaryConnection").await?;
+    if primary.as_str() == "/" {
+        return Ok(None);
+    }
+
+    let active = device_proxy(
+        connection,
+        &primary,
+        "org.freedesktop.NetworkManager.Connection.Active",
+    )
+    .await?;
+    let mut info = ConnectionInfo {
+        id: active.get_property("Id").await?,
+        kind: active.get_property("Type").await?,
+        ip: None,
+        gateway: None,
+        path: primary.to_string(),
+    };
+
+    let ip4: OwnedObjectPath = active.get_property("Ip4Config").await?;
+    if ip4.as_str() != "/" {
+        let config =
+            device_proxy(connection, &ip4, "org.freedesktop.NetworkManager.IP4Config").await?;
+        let addresses: Vec<HashMap<String, OwnedValue>> =
+            config.get_property("AddressData").await?;
+        info.ip = addresses.first().and_then(|addr| {
+            let ip = String::try_from(addr.get("address")?.clone()).ok()?;
+            let prefix = u32::try_from(addr.get("prefix")?.clone()).ok()?;
+            Some(format!("{ip}/{prefix}"))
+        });
+        info.gateway = config
+            .get_property::<String>("Gateway")
+            .await
+            .ok()
+            .filter(|gw| !gw.is_empty());
+    }
+
+    Ok(Some(info))
+}
+
+/// Returns details of the primary connection using NetworkManager on the
+/// system bus
+pub async fn connection_info() -> Result<Option<ConnectionInfo>> {
     let connection = Connection::system().await?;
+    connection_info_on(&connection).await
+}
+
+/// Deactivates the active connection at `path`. Its profile isn't removed
+pub async fn disconnect_on(connection: &Connection, path: &str) -> Result<()> {
+    let nm = nm_proxy(connection).await?;
+    nm.call_method("DeactivateConnection", &(ObjectPath::try_from(path)?))
+        .await?;
+    Ok(())
+}
+
+pub async fn disconnect(path: &str) -> Result<()> {
+    let connection = Connection::system().await?;
+    disconnect_on(&connection, path).await
+}
+
+/// Max time to wait for the activation of the Wi-Fi connection
+pub const CONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// `NM_ACTIVE_CONNECTION_STATE_ACTIVATED`
+const NM_ACTIVE_CONNECTION_STATE_ACTIVATED: u32 = 2;
+
+/// `NM_ACTIVE_CONNECTION_STATE_DEACTIVATED`
+const NM_ACTIVE_CONNECTION_STATE_DEACTIVATED: u32 = 4;
+
+/// `NM_ACTIVE_CONNECTION_STATE_REASON_NO_SECRETS`: the supplicant has
+/// rejected the password and there is no secret agent to ask another one
+const NM_ACTIVE_CONNECTION_STATE_REASON_NO_SECRETS: u32 = 9;
+
+/// `NM_ACTIVE_CONNECTION_STATE_REASON_LOGIN_FAILED`
+const NM_ACTIVE_CONNECTION_STATE_REASON_LOGIN_FAILED: u32 = 10;
+
+/// Result of the connection to the Wi-Fi network
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectStatus {
+    Connected,
+    WrongPassword,
+    Timeout,
+
+    /// Activation has failed (`NMActiveConnectionStateReason`)
+    Failed(u32),
+}
+
+/// NetworkManager connection settings (`a{sa{sv}}`)
+pub type Settings = HashMap<&'static str, HashMap<&'static str, Value<'static>>>;
+
+/// Creates settings of the new Wi-Fi connection profile
+pub fn wifi_settings(ssid: &str, security: Security, pass: Option<&str>) -> Result<Settings> {
+    let mut settings = Settings::new();
+    settings.insert(
+        "connection",
+        HashMap::from([
+            ("id", Value::from(ssid.to_string())),
+            ("type", Value::from("802-11-wireless")),
+            ("autoconnect", Value::from(true)),
+        ]),
+    );
+    settings.insert(
+        "802-11-wireless",
+        HashMap::from([
+            ("ssid", Value::from(ssid.as_bytes().to_vec())),
+            ("mode", Value::from("infrastructure")),
+        ]),
+    );
+
+    let key_mgmt = match security {
+        Security::Open => return Ok(settings),
+        Security::WpaPsk => "wpa-psk",
+        Security::Sae => "sae",
+        Security::Wep | Security::Enterprise => {
+            return Err(anyhow!(tr!("error-wifi-security")));
+        }
+    };
+    let pass = check_password(pass, security)?;
+
+    settings.insert(
+        "802-11-wireless-security",
+        HashMap::from([
+            ("key-mgmt", Value::from(key_mgmt)),
+            ("psk", Value::from(pass.to_string())),
+        ]),
+    );
+    Ok(settings)
+}
+
+/// Checks the length of the password. WPA/WPA2 accepts the passphrase of
+/// 8-63 bytes or the raw PSK of 64 hex digits, WPA3 (SAE) has no upper
+/// limit
+pub fn check_password(pass: Option<&str>, security: Security) -> Result<&str> {
+    let pass = pass
+        .filter(|pass| !pass.is_empty())
+        .ok_or_else(|| anyhow!(tr!("error-wifi-no-password")))?;
+    let raw_psk = pass.len() == 64 && pass.chars().all(|c| c.is_ascii_hexdigit());
+
+    match security {
+        Security::Sae if pass.len() < 8 => Err(anyhow!(tr!("error-wifi-password-short"))),
+        Security::Sae => Ok(pass),
+        _ if (8..=63).contains(&pass.len()) || raw_psk => Ok(pass),
+        _ => Err(anyhow!(tr!("error-wifi-password-length"))),
+    }
+}
+
+/// Subscribes to the state changes of all active connections
+async fn activation_states(connection: &Connection) -> Result<zbus::MessageStream> {
+    let states = zbus::MessageStream::for_match_rule(
+        zbus::MatchRule::builder()
+            .msg_type(zbus::message::Type::Signal)
+            .interface("org.freedesktop.NetworkManager.Connection.Active")?
+            .member("StateChanged")?
+            .build(),
+        connection,
+        None,
+    )
+    .await?;
+    Ok(states)
+}
+
+/// Waits for the activation of the connection at `active` path. `states`
+/// must be subscribed before the activation to not miss the signal
+async fn wait_activation(
+    states: zbus::MessageStream,
+    active: &OwnedObjectPath,
+    timeout: Duration,
+) -> ConnectStatus {
+    let mut wait = std::pin::pin!(states.filter_map(|msg| async move {
+        let msg = msg.ok()?;
+        if msg.header().path()?.as_str() != active.as_str() {
+            return None;
+        }
+        let (state, reason) = msg.body().deserialize::<(u32, u32)>().ok()?;
+        match state {
+            NM_ACTIVE_CONNECTION_STATE_ACTIVATED => Some(ConnectStatus::Connected),
+            NM_ACTIVE_CONNECTION_STATE_DEACTIVATED => Some(match reason {
+                NM_ACTIVE_CONNECTION_STATE_REASON_NO_SECRETS
+                | NM_ACTIVE_CONNECTION_STATE_REASON_LOGIN_FAILED => ConnectStatus::WrongPassword,
+                reason => ConnectStatus::Failed(reason),
+            }),
+            _ => None,
+        }
+    }));
+
+    match tokio::time::timeout(timeout, wait.next()).await {
+        Ok(Some(status)) => status,
+        Ok(None) | Err(_) => ConnectStatus::Timeout,
+    }
+}
+
+/// Creates the connection profile with given `settings`, activates it on
+/// the `device` and waits for the result. Unsuccessful profile is removed
+pub async fn activate_on(
+    connection: &Connection,
+    settings: Settings,
+    device: &str,
+    specific_object: &str,
+    timeout: Duration,
+) -> Result<ConnectStatus> {
+    add_and_activate(connection, settings, device, specific_object, timeout)
+        .await
+        .map(|(status, _)| status)
+}
+
+/// Same as [`activate_on`], but also returns the path of the new profile
+async fn add_and_activate(
+    connection: &Connection,
+    settings: Settings,
+    device: &str,
+    specific_object: &str,
+    timeout: Duration,
+) -> Result<(ConnectStatus, OwnedObjectPath)> {
+    let nm = nm_proxy(connection).await?;
+    let states = activation_states(connection).await?;
+
+    let (profile, active): (OwnedObjectPath, OwnedObjectPath) = nm
+        .call(
+            "AddAndActivateConnection",
+            &(
+                settings,
+                ObjectPath::try_from(device)?,
+                ObjectPath::try_from(specific_object)?,
+            ),
+        )
+        .await?;
+
+    let status = wait_activation(states, &active, timeout).await;
+    if status != ConnectStatus::Connected {
+        // The profile with wrong password would be autoconnected again and again
+        let _ = delete_profile(connection, &profile).await;
+    }
+    Ok((status, profile))
+}
+
+async fn delete_profile(connection: &Connection, profile: &OwnedObjectPath) -> Result<()> {
+    let proxy = device_proxy(
+        connection,
+        profile,
+        "org.freedesktop.NetworkManager.Settings.Connection",
+    )
+    .await?;
+    proxy.call_method("Delete", &()).await?;
+    Ok(())
+}
+
+/// Connects to the access point found by [`scan_wifi_on`]. `pass` is
+/// required for secured networks
+pub async fn connect_wifi_on(
+    connection: &Connection,
+    ap: &AccessPoint,
+    pass: Option<&str>,
+    timeout: Duration,
+) -> Result<ConnectStatus> {
+    let settings = wifi_settings(&ap.ssid, ap.security, pass)?;
+    activate_on(connection, settings, &ap.device, &ap.path, timeout).await
+}
+
+/// Connects to the access point using NetworkManager on the system bus
+pub async fn connect_wifi(ap: &AccessPoint, pass: Option<&str>) -> Result<ConnectStatus> {
+    let connection = Connection::system().await?;
+    connect_wifi_on(&connection, ap, pass, CONNECT_TIMEOUT).await
+}
+
+/// Connects to the network which doesn't broadcast its SSID. It's
+/// activated on the first Wi-Fi device
+pub async fn connect_hidden_on(
+    connection: &Connection,
+    ssid: &str,
+    security: Security,
+    pass: Option<&str>,
+    timeout: Duration,
+) -> Result<ConnectStatus> {
+    if ssid.is_empty() {
+        return Err(anyhow!(tr!("error-wifi-no-ssid")));
+    }
+    let mut settings = wifi_settings(ssid, security, pass)?;
+    if let Some(wireless) = settings.get_mut("802-11-wireless") {
+        wireless.insert("hidden", Value::from(true));
+    }
+
+    let device = wifi_devices(connection)
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!(tr!("error-wifi-no-adapter")))?;
+    activate_on(connection, settings, &device, "/", timeout).await
+}
+
+pub async fn connect_hidden(
+    ssid: &str,
+    security: Security,
+    pass: Option<&str>,
+) -> Result<ConnectStatus> {
+    let connection = Connection::system().await?;
+    connect_hidden_on(&connection, ssid, security, pass, CONNECT_TIMEOUT).await
+}
+
+/// Range of the autoconnect priority of the connection profile
+pub const PRIORITY_RANGE: std::ops::RangeInclusive<i32> = -999..=999;
+
+/// Connection settings as they are returned by NetworkManager
+type OwnedSettings = HashMap<String, HashMap<String, OwnedValue>>;
+
+/// `NM_SETTINGS_UPDATE2_FLAG_TO_DISK`
+const NM_SETTINGS_UPDATE2_FLAG_TO_DISK: u32 = 0x1;
+
+/// Saved Wi-Fi connection profile
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SavedNetwork {
+    /// Name of the profile
+    pub id: String,
+    pub ssid: String,
+    pub security: Security,
+
+    /// The network doesn't broadcast its SSID
+    pub hidden: bool,
+    pub autoconnect: bool,
+
+    /// Profiles with higher priority are autoconnected first
+    pub priority: i32,
+
+    /// D-Bus object path of the profile
+    pub path: String,
+}
+
+fn setting<T: TryFrom<OwnedValue>>(settings: &OwnedSettings, group: &str, key: &str) -> Option<T> {
+    T::try_from(settings.get(group)?.get(key)?.try_clone().ok()?).ok()
+}
+
+impl SavedNetwork {
+    fn from_settings(path: &OwnedObjectPath, settings: &OwnedSettings) -> Option<Self> {
+        if setting::<String>(settings, "connection", "type")? != "802-11-wireless" {
+            return None;
+        }
+        let ssid: Vec<u8> = setting(settings, "802-11-wireless", "ssid")?;
+
+        Some(Self {
+            id: setting(settings, "connection", "id")?,
+            ssid: String::from_utf8_lossy(&ssid).to_string(),
+            security: Security::from_key_mgmt(
+                setting::<String>(settings, "802-11-wireless-security", "key-mgmt").as_deref(),
+            ),
+            hidden: setting(settings, "802-11-wireless", "hidden").unwrap_or(false),
+            autoconnect: setting(settings, "connection", "autoconnect").unwrap_or(true),
+            priority: setting(settings, "connection", "autoconnect-priority").unwrap_or(0),
+            path: path.to_string(),
+        })
+    }
+}
+
+async fn profile_proxy<'a>(
+    connection: &Connection,
+    path: &'a OwnedObjectPath,
+) -> Result<zbus::Proxy<'a>> {
+    device_proxy(
+        connection,
+        path,
+        "org.freedesktop.NetworkManager.Settings.Connection",
+    )
+    .await
+}
+
+/// Returns all saved Wi-Fi profiles. Profiles with higher priority go first
+pub async fn saved_networks_on(connection: &Connection) -> Result<Vec<SavedNetwork>> {
     let settings = zbus::Proxy::new(
-        &connection,
+        connection,
         "org.freedesktop.NetworkManager",
         "/org/freedesktop/NetworkManager/Settings",
         "org.freedesktop.NetworkManager.Settings",
     )
     .await?;
+    let profiles: Vec<OwnedObjectPath> = settings.call("ListConnections", &()).await?;
 
-    let mut config = HashMap::new();
-    config.insert("ssid", Value::new(ssid));
-    config.insert("psk", Value::new(pass));
+    let mut networks = vec![];
+    for path in profiles {
+        let profile = profile_proxy(connection, &path).await?;
+        let settings: OwnedSettings = profile.call("GetSettings", &()).await?;
+        networks.extend(SavedNetwork::from_settings(&path, &settings));
+    }
+    networks.sort_by(|a, b| b.priority.cmp(&a.priority).then_with(|| a.id.cmp(&b.id)));
+    Ok(networks)
+}
+
+pub async fn saved_networks() -> Result<Vec<SavedNetwork>> {
+    let connection = Connection::system().await?;
+    saved_networks_on(&connection).await
+}
+
+/// Removes the saved profile. NetworkManager disconnects from the network
+/// if it's active
+pub async fn forget_network_on(connection: &Connection, path: &str) -> Result<()> {
+    delete_profile(connection, &OwnedObjectPath::try_from(path)?).await
+}
+
+pub async fn forget_network(path: &str) -> Result<()> {
+    let connection = Connection::system().await?;
+    forget_network_on(&connection, path).await
+}
+
+/// Changes settings of the saved profile with `modify`. `Update2` replaces
+/// the settings, but keeps the stored secrets if the new ones contain no
+/// secrets at all. So the secrets aren't requested and sent back (they may
+/// be owned by the agent of the user session and unavailable)
+async fn update_profile(
+    connection: &Connection,
+    path: &str,
+    modify: impl FnOnce(&mut OwnedSettings) -> Result<()>,
+) -> Result<()> {
+    let path = OwnedObjectPath::try_from(path)?;
+    let profile = profile_proxy(connection, &path).await?;
+    let mut settings: OwnedSettings = profile.call("GetSettings", &()).await?;
 
-    settings.call_method("AddConnection", &(config)).await?;
+    modify(&mut settings)?;
+    let args: HashMap<&str, Value> = HashMap::new();
+    let _: HashMap<String, OwnedValue> = profile
+        .call(
+            "Update2",
+            &(settings, NM_SETTINGS_UPDATE2_FLAG_TO_DISK, args),
+        )
+        .await?;
     Ok(())
 }
+
+/// Changes the password of the saved WPA/WPA3 Personal network
+pub async fn set_password_on(connection: &Connection, path: &str, pass: &str) -> Result<()> {
+    update_profile(connection, path, move |settings| {
+        let security = settings
+            .get_mut("802-11-wireless-security")
+            .ok_or_else(|| anyhow!(tr!("error-wifi-open")))?;
+        let sae = security
+            .get("key-mgmt")
+            .is_some_and(|key_mgmt| matches!(&**key_mgmt, Value::Str(s) if s.as_str() == "sae"));
+        let kind = if sae { Security::Sae } else { Security::WpaPsk };
+
+        let pass = check_password(Some(pass), kind)?;
+        security.insert("psk".to_string(), Value::from(pass.to_string()).try_into()?);
+        Ok(())
+    })
+    .await
+}
+
+pub async fn set_password(path: &str, pass: &str) -> Result<()> {
+    let connection = Connection::system().await?;
+    set_password_on(&connection, path, pass).await
+}
+
+/// Changes the autoconnect flag and priority of the saved network
+pub async fn set_autoconnect_on(
+    connection: &Connection,
+    path: &str,
+    autoconnect: bool,
+    priority: i32,
+) -> Result<()> {
+    if !PRIORITY_RANGE.contains(&priority) {
+        return Err(anyhow!(tr!(
+            "error-wifi-priority",
+            min = *PRIORITY_RANGE.start(),
+            max = *PRIORITY_RANGE.end()
+        )));
+    }
+    update_profile(connection, path, move |settings| {
+        let conn = settings.entry("connection".to_string()).or_default();
+        conn.insert("autoconnect".to_string(), OwnedValue::from(autoconnect));
+        conn.insert(
+            "autoconnect-priority".to_string(),
+            OwnedValue::from(priority),
+        );
+        Ok(())
+    })
+    .await
+}
+
+pub async fn set_autoconnect(path: &str, autoconnect: bool, priority: i32) -> Result<()> {
+    let connection = Connection::system().await?;
+    set_autoconnect_on(&connection, path, autoconnect, priority).await
+}
+
+/// Activates the saved profile on the first Wi-Fi device. Unlike
+/// [`activate_on`] the profile isn't removed on failure
+pub async fn activate_saved_on(
+    connection: &Connection,
+    path: &str,
+    timeout: Duration,
+) -> Result<ConnectStatus> {
+    let nm = nm_proxy(connection).await?;
+    let states = activation_states(connection).await?;
+
+    let device = wifi_devices(connection)
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!(tr!("error-wifi-no-adapter")))?;
+    let active: OwnedObjectPath = nm
+        .call(
+            "ActivateConnection",
+            &(
+                ObjectPath::try_from(path)?,
+                device,
+                ObjectPath::try_from("/")?,
+            ),
+        )
+        .await?;
+
+    Ok(wait_activation(states, &active, timeout).await)
+}
+
+pub async fn activate_saved(path: &str) -> Result<ConnectStatus> {
+    let connection = Connection::system().await?;
+    activate_saved_on(&connection, path, CONNECT_TIMEOUT).await
+}
+
+/// Address of the device in the hotspot (NetworkManager's default for the
+/// `shared` IPv4 method)
+pub const HOTSPOT_ADDR: &str = "10.42.0.1";
+
+/// Access point brought up by the device itself
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hotspot {
+    pub ssid: String,
+
+    /// D-Bus object path of the hotspot profile
+    pub profile: String,
+}
+
+/// Creates settings of the access point profile. Clients get addresses
+/// from the NetworkManager's DHCP server. The hotspot is open if `pass`
+/// isn't set
+pub fn hotspot_settings(ssid: &str, pass: Option<&str>) -> Result<Settings> {
+    let mut settings = Settings::new();
+    settings.insert(
+        "connection",
+        HashMap::from([
+            ("id", Value::from(format!("{ssid} (hotspot)"))),
+            ("type", Value::from("802-11-wireless")),
+            ("autoconnect", Value::from(false)),
+        ]),
+    );
+    settings.insert(
+        "802-11-wireless",
+        HashMap::from([
+            ("ssid", Value::from(ssid.as_bytes().to_vec())),
+            ("mode", Value::from("ap")),
+            ("band", Value::from("bg")),
+        ]),
+    );
+    settings.insert("ipv4", HashMap::from([("method", Value::from("shared"))]));
+    settings.insert("ipv6", HashMap::from([("method", Value::from("ignore"))]));
+
+    if let Some(pass) = pass {
+        let pass = check_password(Some(pass), Security::WpaPsk)?;
+        settings.insert(
+            "802-11-wireless-security",
+            HashMap::from([
+                ("key-mgmt", Value::from("wpa-psk")),
+                ("proto", Value::from(vec!["rsn"])),
+                ("pairwise", Value::from(vec!["ccmp"])),
+                ("group", Value::from(vec!["ccmp"])),
+                ("psk", Value::from(pass.to_string())),
+            ]),
+        );
+    }
+    Ok(settings)
+}
+
+/// Brings up the access point on the first Wi-Fi device. The active
+/// client connection of this device is replaced by the hotspot
+pub async fn start_hotspot_on(
+    connection: &Connection,
+    ssid: &str,
+    pass: Option<&str>,
+    timeout: Duration,
+) -> Result<Hotspot> {
+    let settings = hotspot_settings(ssid, pass)?;
+    let device = wifi_devices(connection)
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!(tr!("error-wifi-no-adapter")))?;
+
+    match add_and_activate(connection, settings, &device, "/", timeout).await? {
+        (ConnectStatus::Connected, profile) => Ok(Hotspot {
+            ssid: ssid.to_string(),
+            profile: profile.to_string(),
+        }),
+        (status, _) => Err(anyhow!(tr!(
+            "error-hotspot",
+            status = format!("{status:?}")
+        ))),
+    }
+}
+
+pub async fn start_hotspot(ssid: &str, pass: Option<&str>) -> Result<Hotspot> {
+    let connection = Connection::system().await?;
+    start_hotspot_on(&connection, ssid, pass, CONNECT_TIMEOUT).await
+}
+
+/// Stops the access point and removes its profile
+pub async fn stop_hotspot_on(connection: &Connection, hotspot: &Hotspot) -> Result<()> {
+    forget_network_on(connection, &hotspot.profile).await
+}
+
+pub async fn stop_hotspot(hotspot: &Hotspot) -> Result<()> {
+    let connection = Connection::system().await?;
+    stop_hotspot_on(&connection, hotspot).await
+}
+
+#[cfg(test)]
+mod mock;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[tokio::test]
+    async fn scan_test() {
+        let (connection, nm) = mock::NetworkManager::start().await;
+        let points = scan_wifi_on(&connection, Duration::from_secs(2))
+            .await
+            .unwrap();
+
+        assert!(nm.scanned());
+        assert_eq!(
+            points
+                .iter()
+                .map(|ap| (ap.ssid.as_str(), ap.strength, ap.security, ap.in_use))
+                .collect::<Vec<_>>(),
+            vec![
+                ("Office", 40, Security::WpaPsk, true),
+                ("Cafe", 70, Security::Open, false),
+                ("Home", 55, Security::Sae, false),
+            ]
+        );
+        assert_eq!(points[1].frequency, 5180);
+    }
+
+    #[test]
+    fn settings_test() {
+        let settings = wifi_settings("Home", Security::Sae, Some("password")).unwrap();
+        assert_eq!(
+            settings["802-11-wireless"]["ssid"],
+            Value::from(b"Home".to_vec())
+        );
+        assert_eq!(
+            settings["802-11-wireless-security"]["key-mgmt"],
+            Value::from("sae")
+        );
+
+        let settings = wifi_settings("Cafe", Security::Open, None).unwrap();
+        assert!(!settings.contains_key("802-11-wireless-security"));
+
+        assert!(wifi_settings("Office", Security::WpaPsk, None).is_err());
+        assert!(wifi_settings("Office", Security::WpaPsk, Some("short")).is_err());
+        assert!(wifi_settings("Corp", Security::Enterprise, Some("password")).is_err());
+    }
+
+    #[test]
+    fn password_test() {
+        let psk = "0123456789abcdef".repeat(4);
+        let long = "x".repeat(100);
+
+        assert!(check_password(None, Security::WpaPsk).is_err());
+        assert!(check_password(Some(""), Security::Sae).is_err());
+        assert!(check_password(Some("short"), Security::WpaPsk).is_err());
+        assert!(check_password(Some("short"), Security::Sae).is_err());
+        assert!(check_password(Some("password"), Security::WpaPsk).is_ok());
+        assert!(check_password(Some(&psk), Security::WpaPsk).is_ok());
+        assert!(check_password(Some(&psk.replace('a', "g")), Security::WpaPsk).is_err());
+        assert!(check_password(Some(&long), Security::WpaPsk).is_err());
+        assert!(check_password(Some(&long), Security::Sae).is_ok());
+        // The length is counted in bytes: 32 Cyrillic letters are 64 bytes
+        assert!(check_password(Some(&"ж".repeat(32)), Security::WpaPsk).is_err());
+    }
+
+    #[tokio::test]
+    async fn connect_test() {
+        let (connection, _nm) = mock::NetworkManager::start().await;
+        let points = scan_wifi_on(&connection, Duration::from_secs(2))
+            .await
+            .unwrap();
+        let office = &points[0];
+        let timeout = Duration::from_secs(2);
+
+        let status = connect_wifi_on(&connection, office, Some(mock::PASSWORD), timeout).await;
+        assert_eq!(status.unwrap(), ConnectStatus::Connected);
+
+        let status = connect_wifi_on(&connection, office, Some("wrong password"), timeout).await;
+        assert_eq!(status.unwrap(), ConnectStatus::WrongPassword);
+
+        let status = connect_wifi_on(&connection, office, Some(mock::SLOW_PASSWORD), timeout).await;
+        assert_eq!(status.unwrap(), ConnectStatus::Timeout);
+    }
+
+    #[tokio::test]
+    async fn connection_info_test() {
+        let (connection, _nm) = mock::NetworkManager::start().await;
+        let info = connection_info_on(&connection).await.unwrap().unwrap();
+
+        assert!(info.is_wifi());
+        assert_eq!(info.id, "Office");
+        assert_eq!(info.ip.as_deref(), Some("192.168.1.10/24"));
+        assert_eq!(info.gateway.as_deref(), Some("192.168.1.1"));
+
+        disconnect_on(&connection, &info.path).await.unwrap();
+        assert_eq!(connection_info_on(&connection).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn saved_networks_test() {
+        let (connection, nm) = mock::NetworkManager::start().await;
+        let saved = saved_networks_on(&connection).await.unwrap();
+        assert_eq!(
+            saved
+                .iter()
+                .map(|net| (net.ssid.as_str(), net.security, net.hidden, net.priority))
+                .collect::<Vec<_>>(),
+            vec![
+                ("Office", Security::WpaPsk, false, 10),
+                ("Home", Security::Sae, false, 0),
+                ("Lab", Security::WpaPsk, true, 0),
+            ]
+        );
+        let home = &saved[1].path;
+
+        // The password is kept when other settings are changed
+        set_autoconnect_on(&connection, home, false, 20)
+            .await
+            .unwrap();
+        assert!(
+            set_autoconnect_on(&connection, home, true, 1000)
+                .await
+                .is_err()
+        );
+        assert_eq!(
+            nm.setting(home, "802-11-wireless-security", "psk"),
+            Some(Value::from(mock::PASSWORD).try_into().unwrap())
+        );
+
+        set_password_on(&connection, home, "new password")
+            .await
+            .unwrap();
+        assert!(set_password_on(&connection, home, "short").await.is_err());
+        assert_eq!(
+            nm.setting(home, "802-11-wireless-security", "psk"),
+            Some(Value::from("new password").try_into().unwrap())
+        );
+
+        let saved = saved_networks_on(&connection).await.unwrap();
+        assert_eq!(
+            (saved[0].ssid.as_str(), saved[0].autoconnect),
+            ("Home", false)
+        );
+
+        forget_network_on(&connection, home).await.unwrap();
+        assert_eq!(saved_networks_on(&connection).await.unwrap().len(), 2);
+
+        let status = activate_saved_on(&connection, &saved[1].path, Duration::from_secs(2)).await;
+        assert_eq!(status.unwrap(), ConnectStatus::Connected);
+    }
+
+    #[tokio::test]
+    async fn hidden_test() {
+        let (connection, nm) = mock::NetworkManager::start().await;
+        let timeout = Duration::from_secs(2);
+
+        let status = connect_hidden_on(
+            &connection,
+            "Lab",
+            Security::WpaPsk,
+            Some(mock::PASSWORD),
+            timeout,
+        )
+        .await;
+        assert_eq!(status.unwrap(), ConnectStatus::Connected);
+        assert_eq!(
+            nm.added("802-11-wireless", "hidden"),
+            Some(OwnedValue::from(true))
+        );
+
+        assert!(
+            connect_hidden_on(&connection, "", Security::Open, None, timeout)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn hotspot_test() {
+        let (connection, nm) = mock::NetworkManager::start().await;
+        assert!(
+            wait_connected_on(&connection, Duration::from_secs(1))
+                .await
+                .unwrap()
+        );
+
+        let hotspot = start_hotspot_on(&connection, "Ice-Setup", None, Duration::from_secs(2))
+            .await
+            .unwrap();
+        assert_eq!(
+            nm.added("802-11-wireless", "mode"),
+            Some(Value::from("ap").try_into().unwrap())
+        );
+        assert!(nm.added("802-11-wireless-security", "psk").is_none());
+
+        stop_hotspot_on(&connection, &hotspot).await.unwrap();
+        assert!(nm.setting(&hotspot.profile, "connection", "id").is_none());
+
+        assert!(hotspot_settings("Ice-Setup", Some("short")).is_err());
+        let info = connection_info_on(&connection).await.unwrap().unwrap();
+        disconnect_on(&connection, &info.path).await.unwrap();
+        assert!(
+            !wait_connected_on(&connection, Duration::from_secs(1))
+                .await
+                .unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn network_changes_test() {
+        let (connection, nm) = mock::NetworkManager::start().await;
+        let changes = network_changes_on(&connection).await.unwrap();
+        let mut changes = std::pin::pin!(changes);
+        let mut next = async || {
+            tokio::time::timeout(Duration::from_secs(2), changes.next())
+                .await
+                .unwrap()
+                .unwrap()
+        };
+
+        let mut current = vec![];
+        for _ in 0..4 {
+            current.push(next().await);
+        }
+        for change in [
+            NetworkChange::State(NM_STATE_CONNECTED_GLOBAL),
+            NetworkChange::Connectivity(Connectivity::Full),
+            NetworkChange::PrimaryConnection(mock::ACTIVE_CONNECTION.to_string()),
+            NetworkChange::WifiSignal(Some(40)),
+        ] {
+            assert!(current.contains(&change), "{change:?} isn't sent");
+        }
+
+        // Only the active access point is tracked
+        nm.set_strength(2, 10).await;
+        nm.set_strength(0, 35).await;
+        assert_eq!(next().await, NetworkChange::WifiSignal(Some(35)));
+
+        disconnect_on(&connection, mock::ACTIVE_CONNECTION)
+            .await
+            .unwrap();
+        let (a, b) = (next().await, next().await);
+        assert!(
+            [&a, &b].contains(&&NetworkChange::PrimaryConnection("/".to_string()))
+                && [&a, &b].contains(&&NetworkChange::Connectivity(Connectivity::None))
+        );
+    }
+}
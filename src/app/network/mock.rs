@@ -0,0 +1,584 @@
+//! Mock NetworkManager served over the peer-to-peer D-Bus connection
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    os::unix::net::UnixStream,
+    sync::{
+        Arc, Mutex,
+        atomic::{AtomicBool, Ordering},
+    },
+};
+use zbus::{
+    Connection,
+    connection::Builder,
+    interface,
+    object_server::SignalEmitter,
+    zvariant::{OwnedObjectPath, OwnedValue, Value},
+};
+
+fn path(path: &str) -> OwnedObjectPath {
+    OwnedObjectPath::try_from(path).unwrap()
+}
+
+/// Password which is accepted by all secured networks
+pub const PASSWORD: &str = "correct horse";
+
+/// With this password the activation never ends
+pub const SLOW_PASSWORD: &str = "slow password";
+
+pub const ACTIVE_CONNECTION: &str = "/org/freedesktop/NetworkManager/ActiveConnection/1";
+/// Profile created by `AddAndActivateConnection` (it's replaced by every
+/// new one). Saved profiles start from `Settings/10`
+const PROFILE: &str = "/org/freedesktop/NetworkManager/Settings/1";
+const IP4_CONFIG: &str = "/org/freedesktop/NetworkManager/IP4Config/1";
+
+type Settings = HashMap<String, HashMap<String, OwnedValue>>;
+
+/// Saved profiles by their paths
+type Profiles = Arc<Mutex<BTreeMap<String, Settings>>>;
+
+fn value<'a>(value: impl Into<Value<'a>>) -> OwnedValue {
+    OwnedValue::try_from(value.into()).unwrap()
+}
+
+fn clone_settings(settings: &Settings) -> Settings {
+    settings
+        .iter()
+        .map(|(group, values)| {
+            let values = values
+                .iter()
+                .map(|(key, value)| (key.clone(), value.try_clone().unwrap()))
+                .collect();
+            (group.clone(), values)
+        })
+        .collect()
+}
+
+/// Settings of the saved profile. `psk` is set for the secured networks
+fn profile(id: &str, kind: &str, key_mgmt: Option<&str>, priority: i32, hidden: bool) -> Settings {
+    let mut settings = Settings::from([(
+        "connection".to_string(),
+        HashMap::from([
+            ("id".to_string(), value(id)),
+            ("type".to_string(), value(kind)),
+            ("autoconnect-priority".to_string(), value(priority)),
+        ]),
+    )]);
+    if kind == "802-11-wireless" {
+        settings.insert(
+            "802-11-wireless".to_string(),
+            HashMap::from([
+                ("ssid".to_string(), value(id.as_bytes().to_vec())),
+                ("hidden".to_string(), value(hidden)),
+            ]),
+        );
+    }
+    if let Some(key_mgmt) = key_mgmt {
+        settings.insert(
+            "802-11-wireless-security".to_string(),
+            HashMap::from([
+                ("key-mgmt".to_string(), value(key_mgmt)),
+                ("psk".to_string(), value(PASSWORD)),
+            ]),
+        );
+    }
+    settings
+}
+
+async fn emit_state(conn: &Connection, state: (u32, u32)) -> zbus::fdo::Result<()> {
+    conn.emit_signal(
+        None::<()>,
+        ACTIVE_CONNECTION,
+        "org.freedesktop.NetworkManager.Connection.Active",
+        "StateChanged",
+        &state,
+    )
+    .await?;
+    Ok(())
+}
+
+struct Root {
+    devices: Vec<OwnedObjectPath>,
+    primary_connection: OwnedObjectPath,
+    state: u32,
+    connectivity: u32,
+    profiles: Profiles,
+    added: Arc<Mutex<Option<Settings>>>,
+}
+
+#[interface(name = "org.freedesktop.NetworkManager")]
+impl Root {
+    fn get_devices(&self) -> Vec<OwnedObjectPath> {
+        self.devices.clone()
+    }
+
+    #[zbus(name = "state")]
+    fn state(&self) -> u32 {
+        self.state
+    }
+
+    #[zbus(property)]
+    fn connectivity(&self) -> u32 {
+        self.connectivity
+    }
+
+    #[zbus(property)]
+    fn primary_connection(&self) -> OwnedObjectPath {
+        self.primary_connection.clone()
+    }
+
+    async fn deactivate_connection(
+        &mut self,
+        active: OwnedObjectPath,
+        #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+    ) -> zbus::fdo::Result<()> {
+        if active != self.primary_connection {
+            return Err(zbus::fdo::Error::UnknownObject(active.to_string()));
+        }
+        self.primary_connection = path("/");
+        self.state = 20; // NM_STATE_DISCONNECTED
+        self.connectivity = 1; // NM_CONNECTIVITY_NONE
+        let _ = self.primary_connection_changed(&emitter).await;
+        let _ = self.connectivity_changed(&emitter).await;
+        Ok(())
+    }
+
+    async fn add_and_activate_connection(
+        &self,
+        settings: HashMap<String, HashMap<String, OwnedValue>>,
+        _device: OwnedObjectPath,
+        _specific_object: OwnedObjectPath,
+        #[zbus(connection)] conn: &Connection,
+    ) -> zbus::fdo::Result<(OwnedObjectPath, OwnedObjectPath)> {
+        let psk = settings
+            .get("802-11-wireless-security")
+            .and_then(|security| security.get("psk"))
+            .and_then(|psk| String::try_from(psk.clone()).ok());
+
+        // (state, reason) of the active connection
+        let result = match psk.as_deref() {
+            None | Some(PASSWORD) => Some((2u32, 1u32)),
+            Some(SLOW_PASSWORD) => None,
+            Some(_) => Some((4, 9)),
+        };
+        self.profiles
+            .lock()
+            .unwrap()
+            .insert(PROFILE.to_string(), clone_settings(&settings));
+        conn.object_server()
+            .at(
+                PROFILE,
+                SettingsConnection {
+                    path: PROFILE.to_string(),
+                    profiles: self.profiles.clone(),
+                },
+            )
+            .await?;
+        *self.added.lock().unwrap() = Some(settings);
+
+        if let Some(result) = result {
+            emit_state(conn, result).await?;
+        }
+
+        Ok((path(PROFILE), path(ACTIVE_CONNECTION)))
+    }
+
+    async fn activate_connection(
+        &self,
+        _connection: OwnedObjectPath,
+        _device: OwnedObjectPath,
+        _specific_object: OwnedObjectPath,
+        #[zbus(connection)] conn: &Connection,
+    ) -> zbus::fdo::Result<OwnedObjectPath> {
+        emit_state(conn, (2, 1)).await?;
+        Ok(path(ACTIVE_CONNECTION))
+    }
+}
+
+struct SettingsRoot {
+    profiles: Profiles,
+}
+
+#[interface(name = "org.freedesktop.NetworkManager.Settings")]
+impl SettingsRoot {
+    fn list_connections(&self) -> Vec<OwnedObjectPath> {
+        self.profiles
+            .lock()
+            .unwrap()
+            .keys()
+            .map(|p| path(p))
+            .collect()
+    }
+}
+
+/// Saved profile. Secrets aren't returned at all, as if they are owned by
+/// the secret agent of the user session
+struct SettingsConnection {
+    path: String,
+    profiles: Profiles,
+}
+
+impl SettingsConnection {
+    fn settings(&self) -> zbus::fdo::Result<Settings> {
+        self.profiles
+            .lock()
+            .unwrap()
+            .get(&self.path)
+            .map(clone_settings)
+            .ok_or_else(|| zbus::fdo::Error::UnknownObject(self.path.clone()))
+    }
+}
+
+#[interface(name = "org.freedesktop.NetworkManager.Settings.Connection")]
+impl SettingsConnection {
+    fn get_settings(&self) -> zbus::fdo::Result<Settings> {
+        let mut settings = self.settings()?;
+        if let Some(security) = settings.get_mut("802-11-wireless-security") {
+            security.remove("psk");
+        }
+        Ok(settings)
+    }
+
+    /// Like the real NetworkManager, keeps the stored secrets if the new
+    /// settings contain none
+    fn update2(
+        &self,
+        mut settings: Settings,
+        _flags: u32,
+        _args: HashMap<String, OwnedValue>,
+    ) -> zbus::fdo::Result<HashMap<String, OwnedValue>> {
+        let old = self.settings()?;
+        let has_secrets = settings.values().any(|values| values.contains_key("psk"));
+        if !has_secrets {
+            let psk = old
+                .get("802-11-wireless-security")
+                .and_then(|security| security.get("psk"));
+            if let (Some(security), Some(psk)) = (settings.get_mut("802-11-wireless-security"), psk)
+            {
+                security.insert("psk".to_string(), psk.try_clone().unwrap());
+            }
+        }
+        self.profiles
+            .lock()
+            .unwrap()
+            .insert(self.path.clone(), settings);
+        Ok(HashMap::new())
+    }
+
+    fn delete(&self) -> zbus::fdo::Result<()> {
+        self.profiles
+            .lock()
+            .unwrap()
+            .remove(&self.path)
+            .map(|_| ())
+            .ok_or_else(|| zbus::fdo::Error::UnknownObject(self.path.clone()))
+    }
+}
+
+struct ActiveConnection;
+
+#[interface(name = "org.freedesktop.NetworkManager.Connection.Active")]
+impl ActiveConnection {
+    #[zbus(property)]
+    fn id(&self) -> String {
+        "Office".to_string()
+    }
+
+    #[zbus(property, name = "Type")]
+    fn kind(&self) -> String {
+        "802-11-wireless".to_string()
+    }
+
+    #[zbus(property)]
+    fn ip4_config(&self) -> OwnedObjectPath {
+        path(IP4_CONFIG)
+    }
+}
+
+struct Ip4Config;
+
+#[interface(name = "org.freedesktop.NetworkManager.IP4Config")]
+impl Ip4Config {
+    #[zbus(property)]
+    fn address_data(&self) -> Vec<HashMap<String, OwnedValue>> {
+        vec![HashMap::from([
+            (
+                "address".to_string(),
+                OwnedValue::try_from(zbus::zvariant::Value::from("192.168.1.10")).unwrap(),
+            ),
+            ("prefix".to_string(), OwnedValue::from(24u32)),
+        ])]
+    }
+
+    #[zbus(property)]
+    fn gateway(&self) -> String {
+        "192.168.1.1".to_string()
+    }
+}
+
+struct Device {
+    device_type: u32,
+}
+
+#[interface(name = "org.freedesktop.NetworkManager.Device")]
+impl Device {
+    #[zbus(property)]
+    fn device_type(&self) -> u32 {
+        self.device_type
+    }
+}
+
+struct Wireless {
+    access_points: Vec<OwnedObjectPath>,
+    active: OwnedObjectPath,
+    last_scan: i64,
+    scanned: Arc<AtomicBool>,
+}
+
+#[interface(name = "org.freedesktop.NetworkManager.Device.Wireless")]
+impl Wireless {
+    async fn request_scan(
+        &mut self,
+        _options: HashMap<String, OwnedValue>,
+        #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+    ) {
+        self.last_scan += 1;
+        self.scanned.store(true, Ordering::SeqCst);
+        let _ = self.last_scan_changed(&emitter).await;
+    }
+
+    fn get_all_access_points(&self) -> Vec<OwnedObjectPath> {
+        self.access_points.clone()
+    }
+
+    #[zbus(property)]
+    fn last_scan(&self) -> i64 {
+        self.last_scan
+    }
+
+    #[zbus(property)]
+    fn active_access_point(&self) -> OwnedObjectPath {
+        self.active.clone()
+    }
+}
+
+struct AccessPoint {
+    ssid: &'static str,
+    strength: u8,
+    frequency: u32,
+    flags: u32,
+    rsn_flags: u32,
+}
+
+#[interface(name = "org.freedesktop.NetworkManager.AccessPoint")]
+impl AccessPoint {
+    #[zbus(property)]
+    fn ssid(&self) -> Vec<u8> {
+        self.ssid.as_bytes().to_vec()
+    }
+
+    #[zbus(property)]
+    fn strength(&self) -> u8 {
+        self.strength
+    }
+
+    #[zbus(property)]
+    fn frequency(&self) -> u32 {
+        self.frequency
+    }
+
+    #[zbus(property)]
+    fn flags(&self) -> u32 {
+        self.flags
+    }
+
+    #[zbus(property)]
+    fn wpa_flags(&self) -> u32 {
+        0
+    }
+
+    #[zbus(property)]
+    fn rsn_flags(&self) -> u32 {
+        self.rsn_flags
+    }
+}
+
+/// Running mock with one Ethernet device and one Wi-Fi device which sees
+/// five access points: "Office" (two APs, connected to the weaker one),
+/// open "Cafe", WPA3 "Home" and a hidden network. Saved profiles are
+/// "Office", "Home", hidden "Lab" and a wired connection
+pub struct NetworkManager {
+    scanned: Arc<AtomicBool>,
+    profiles: Profiles,
+    added: Arc<Mutex<Option<Settings>>>,
+    server: Connection,
+}
+
+impl NetworkManager {
+    /// Starts the mock and returns the client connection to it
+    pub async fn start() -> (Connection, Self) {
+        let (client, server) = UnixStream::pair().unwrap();
+        let scanned = Arc::new(AtomicBool::new(false));
+        let added = Arc::new(Mutex::new(None));
+        let profiles: Profiles = Arc::new(Mutex::new(BTreeMap::from([
+            (
+                "/org/freedesktop/NetworkManager/Settings/10".to_string(),
+                profile("Office", "802-11-wireless", Some("wpa-psk"), 10, false),
+            ),
+            (
+                "/org/freedesktop/NetworkManager/Settings/11".to_string(),
+                profile("Home", "802-11-wireless", Some("sae"), 0, false),
+            ),
+            (
+                "/org/freedesktop/NetworkManager/Settings/12".to_string(),
+                profile("Lab", "802-11-wireless", Some("wpa-psk"), 0, true),
+            ),
+            (
+                "/org/freedesktop/NetworkManager/Settings/13".to_string(),
+                profile("Wired connection 1", "802-3-ethernet", None, 0, false),
+            ),
+        ])));
+
+        let access_points = [
+            ("Office", 40, 2412, 0x1, 0x188),
+            ("Office", 60, 2437, 0x1, 0x188),
+            ("Cafe", 70, 5180, 0x0, 0x0),
+            ("Home", 55, 5200, 0x1, 0x488),
+            ("", 90, 2462, 0x1, 0x188),
+        ];
+
+        let guid = zbus::Guid::generate();
+        let mut builder = Builder::async_io_unix_stream(server)
+            .server(guid)
+            .unwrap()
+            .p2p()
+            .serve_at(
+                "/org/freedesktop/NetworkManager",
+                Root {
+                    devices: vec![
+                        path("/org/freedesktop/NetworkManager/Devices/1"),
+                        path("/org/freedesktop/NetworkManager/Devices/2"),
+                    ],
+                    primary_connection: path(ACTIVE_CONNECTION),
+                    state: 70,       // NM_STATE_CONNECTED_GLOBAL
+                    connectivity: 4, // NM_CONNECTIVITY_FULL
+                    profiles: profiles.clone(),
+                    added: added.clone(),
+                },
+            )
+            .unwrap()
+            .serve_at(
+                "/org/freedesktop/NetworkManager/Settings",
+                SettingsRoot {
+                    profiles: profiles.clone(),
+                },
+            )
+            .unwrap()
+            .serve_at(ACTIVE_CONNECTION, ActiveConnection)
+            .unwrap()
+            .serve_at(IP4_CONFIG, Ip4Config)
+            .unwrap()
+            .serve_at(
+                "/org/freedesktop/NetworkManager/Devices/1",
+                Device { device_type: 1 },
+            )
+            .unwrap()
+            .serve_at(
+                "/org/freedesktop/NetworkManager/Devices/2",
+                Device { device_type: 2 },
+            )
+            .unwrap()
+            .serve_at(
+                "/org/freedesktop/NetworkManager/Devices/2",
+                Wireless {
+                    access_points: (0..access_points.len())
+                        .map(|i| path(&format!("/org/freedesktop/NetworkManager/AccessPoint/{i}")))
+                        .collect(),
+                    active: path("/org/freedesktop/NetworkManager/AccessPoint/0"),
+                    last_scan: 0,
+                    scanned: scanned.clone(),
+                },
+            )
+            .unwrap();
+
+        for (i, (ssid, strength, frequency, flags, rsn_flags)) in
+            access_points.into_iter().enumerate()
+        {
+            builder = builder
+                .serve_at(
+                    format!("/org/freedesktop/NetworkManager/AccessPoint/{i}"),
+                    AccessPoint {
+                        ssid,
+                        strength,
+                        frequency,
+                        flags,
+                        rsn_flags,
+                    },
+                )
+                .unwrap();
+        }
+
+        let paths: Vec<String> = profiles.lock().unwrap().keys().cloned().collect();
+        for path in paths {
+            builder = builder
+                .serve_at(
+                    path.clone(),
+                    SettingsConnection {
+                        path,
+                        profiles: profiles.clone(),
+                    },
+                )
+                .unwrap();
+        }
+
+        let (server, client) = tokio::join!(
+            builder.build(),
+            Builder::async_io_unix_stream(client).p2p().build()
+        );
+
+        (
+            client.unwrap(),
+            Self {
+                scanned,
+                profiles,
+                added,
+                server: server.unwrap(),
+            },
+        )
+    }
+
+    pub fn scanned(&self) -> bool {
+        self.scanned.load(Ordering::SeqCst)
+    }
+
+    /// Changes the signal strength of the access point with `index`
+    pub async fn set_strength(&self, index: usize, strength: u8) {
+        let iface = self
+            .server
+            .object_server()
+            .interface::<_, AccessPoint>(format!(
+                "/org/freedesktop/NetworkManager/AccessPoint/{index}"
+            ))
+            .await
+            .unwrap();
+        iface.get_mut().await.strength = strength;
+        iface
+            .get()
+            .await
+            .strength_changed(iface.signal_emitter())
+            .await
+            .unwrap();
+    }
+
+    /// Returns the value from the saved profile at `path` (with secrets)
+    pub fn setting(&self, path: &str, group: &str, key: &str) -> Option<OwnedValue> {
+        let profiles = self.profiles.lock().unwrap();
+        profiles.get(path)?.get(group)?.get(key)?.try_clone().ok()
+    }
+
+    /// Returns the value from the settings of the last added connection
+    pub fn added(&self, group: &str, key: &str) -> Option<OwnedValue> {
+        let added = self.added.lock().unwrap();
+        added.as_ref()?.get(group)?.get(key)?.try_clone().ok()
+    }
+}
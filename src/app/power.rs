@@ -0,0 +1,303 @@
+//! Shutdown, reboot and exit to the console via `systemd-logind`
+
+use anyhow::{Result, anyhow};
+use zbus::{Connection, zvariant::OwnedObjectPath};
+
+use crate::i18n::tr;
+
+/// Action of the power menu
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    PowerOff,
+    Reboot,
+
+    /// Terminate the graphical session (or just exit the program if it
+    /// doesn't run in a session)
+    ExitToConsole,
+}
+
+impl Action {
+    pub const ALL: [Self; 3] = [Self::PowerOff, Self::Reboot, Self::ExitToConsole];
+
+    /// Name of the `org.freedesktop.login1.Manager` method. `Can*` methods
+    /// are named after them
+    fn method(self) -> Option<&'static str> {
+        match self {
+            Self::PowerOff => Some("PowerOff"),
+            Self::Reboot => Some("Reboot"),
+            Self::ExitToConsole => None,
+        }
+    }
+
+    /// Id of the message about the action denied by polkit
+    fn denied_message(self) -> &'static str {
+        match self {
+            Self::PowerOff => "error-power-denied-poweroff",
+            Self::Reboot => "error-power-denied-reboot",
+            Self::ExitToConsole => "error-power-denied-exit",
+        }
+    }
+
+    /// Id of the message about the failed request of the action
+    fn error_message(self) -> &'static str {
+        match self {
+            Self::PowerOff => "error-power-poweroff",
+            Self::Reboot => "error-power-reboot",
+            Self::ExitToConsole => "error-power-exit",
+        }
+    }
+}
+
+/// Whether the user may run the action (result of `CanPowerOff` and
+/// others)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Permission {
+    Allowed,
+
+    /// polkit asks for the password. There is no authentication agent on
+    /// the device, so the action will be denied
+    Challenge,
+    Denied,
+
+    /// The action isn't supported by the system
+    Unsupported,
+}
+
+impl Permission {
+    fn parse(answer: &str) -> Self {
+        match answer {
+            "yes" => Self::Allowed,
+            "challenge" => Self::Challenge,
+            "no" => Self::Denied,
+            _ => Self::Unsupported,
+        }
+    }
+
+    pub fn is_allowed(self) -> bool {
+        self == Self::Allowed
+    }
+}
+
+async fn manager_proxy(connection: &Connection) -> Result<zbus::Proxy<'static>> {
+    let proxy = zbus::Proxy::new(
+        connection,
+        "org.freedesktop.login1",
+        "/org/freedesktop/login1",
+        "org.freedesktop.login1.Manager",
+    )
+    .await?;
+    Ok(proxy)
+}
+
+/// Converts the polkit denial to the readable error
+fn denied(action: Action, err: zbus::Error) -> anyhow::Error {
+    const DENIED: [&str; 2] = [
+        "org.freedesktop.DBus.Error.AccessDenied",
+        "org.freedesktop.DBus.Error.InteractiveAuthorizationRequired",
+    ];
+
+    let is_denied = match &err {
+        zbus::Error::MethodError(name, ..) => DENIED.contains(&name.as_str()),
+        zbus::Error::FDO(err) => matches!(
+            **err,
+            zbus::fdo::Error::AccessDenied(_)
+                | zbus::fdo::Error::InteractiveAuthorizationRequired(_)
+        ),
+        _ => false,
+    };
+    if is_denied {
+        anyhow!(tr!(action.denied_message()))
+    } else {
+        anyhow!(tr!(action.error_message(), error = err.to_string()))
+    }
+}
+
+/// Checks whether the `action` is allowed by polkit
+pub async fn permission_on(connection: &Connection, action: Action) -> Result<Permission> {
+    let Some(method) = action.method() else {
+        return Ok(Permission::Allowed);
+    };
+    let proxy = manager_proxy(connection).await?;
+    let answer: String = proxy
+        .call(format!("Can{method}").as_str(), &())
+        .await
+        .map_err(|err| denied(action, err))?;
+    Ok(Permission::parse(&answer))
+}
+
+/// Checks whether the `action` is allowed using `systemd-logind` on the
+/// system bus
+pub async fn permission(action: Action) -> Result<Permission> {
+    let connection = Connection::system().await?;
+    permission_on(&connection, action).await
+}
+
+/// Checks all actions of the power menu using `systemd-logind` on the
+/// system bus
+pub async fn permissions() -> Result<Vec<(Action, Permission)>> {
+    let connection = Connection::system().await?;
+    let mut permissions = vec![];
+    for action in Action::ALL {
+        permissions.push((action, permission_on(&connection, action).await?));
+    }
+    Ok(permissions)
+}
+
+/// Runs the `action`. Nobody is asked for the password, so the action
+/// fails if polkit requires the authentication
+pub async fn run_on(connection: &Connection, action: Action) -> Result<()> {
+    let proxy = manager_proxy(connection).await?;
+
+    if let Some(method) = action.method() {
+        // `interactive = false`
+        proxy
+            .call::<_, _, ()>(method, &(false))
+            .await
+            .map_err(|err| denied(action, err))?;
+        return Ok(());
+    }
+
+    // The program isn't started in the logind session (e.g. by the
+    // systemd service), so exiting it is enough
+    let Ok(session) = proxy
+        .call::<_, _, OwnedObjectPath>("GetSessionByPID", &(std::process::id()))
+        .await
+    else {
+        return Ok(());
+    };
+    let session = zbus::Proxy::new(
+        connection,
+        "org.freedesktop.login1",
+        session,
+        "org.freedesktop.login1.Session",
+    )
+    .await?;
+    session
+        .call::<_, _, ()>("Terminate", &())
+        .await
+        .map_err(|err| denied(action, err))?;
+    Ok(())
+}
+
+/// Runs the `action` using `systemd-logind` on the system bus
+pub async fn run(action: Action) -> Result<()> {
+    let connection = Connection::system().await?;
+    run_on(&connection, action).await
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::{
+        os::unix::net::UnixStream,
+        sync::{Arc, Mutex},
+    };
+    use zbus::{connection::Builder, interface};
+
+    const SESSION: &str = "/org/freedesktop/login1/session/_31";
+
+    /// Mock logind which allows the shutdown and denies the reboot
+    struct Manager {
+        calls: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[interface(name = "org.freedesktop.login1.Manager")]
+    impl Manager {
+        fn can_power_off(&self) -> String {
+            "yes".to_string()
+        }
+
+        fn can_reboot(&self) -> String {
+            "challenge".to_string()
+        }
+
+        fn power_off(&self, interactive: bool) {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("PowerOff({interactive})"));
+        }
+
+        fn reboot(&self, _interactive: bool) -> zbus::fdo::Result<()> {
+            Err(zbus::fdo::Error::InteractiveAuthorizationRequired(
+                "Interactive authentication required.".to_string(),
+            ))
+        }
+
+        #[zbus(name = "GetSessionByPID")]
+        fn get_session_by_pid(&self, _pid: u32) -> OwnedObjectPath {
+            OwnedObjectPath::try_from(SESSION).unwrap()
+        }
+    }
+
+    struct Session {
+        calls: Arc<Mutex<Vec<String>>>,
+    }
+
+    #[interface(name = "org.freedesktop.login1.Session")]
+    impl Session {
+        fn terminate(&self) {
+            self.calls.lock().unwrap().push("Terminate".to_string());
+        }
+    }
+
+    async fn start() -> (Connection, Connection, Arc<Mutex<Vec<String>>>) {
+        let (client, server) = UnixStream::pair().unwrap();
+        let calls = Arc::new(Mutex::new(vec![]));
+
+        let guid = zbus::Guid::generate();
+        let server = Builder::async_io_unix_stream(server)
+            .server(guid)
+            .unwrap()
+            .p2p()
+            .serve_at(
+                "/org/freedesktop/login1",
+                Manager {
+                    calls: calls.clone(),
+                },
+            )
+            .unwrap()
+            .serve_at(
+                SESSION,
+                Session {
+                    calls: calls.clone(),
+                },
+            )
+            .unwrap()
+            .build();
+        let (server, client) =
+            tokio::join!(server, Builder::async_io_unix_stream(client).p2p().build());
+
+        (client.unwrap(), server.unwrap(), calls)
+    }
+
+    #[tokio::test]
+    async fn power_test() {
+        let (connection, _server, calls) = start().await;
+
+        assert_eq!(
+            permission_on(&connection, Action::PowerOff).await.unwrap(),
+            Permission::Allowed
+        );
+        assert_eq!(
+            permission_on(&connection, Action::Reboot).await.unwrap(),
+            Permission::Challenge
+        );
+        assert!(
+            permission_on(&connection, Action::ExitToConsole)
+                .await
+                .unwrap()
+                .is_allowed()
+        );
+
+        run_on(&connection, Action::PowerOff).await.unwrap();
+        let err = run_on(&connection, Action::Reboot).await.unwrap_err();
+        assert_eq!(err.to_string(), tr!("error-power-denied-reboot"));
+        run_on(&connection, Action::ExitToConsole).await.unwrap();
+
+        assert_eq!(
+            *calls.lock().unwrap(),
+            vec!["PowerOff(false)".to_string(), "Terminate".to_string()]
+        );
+    }
+}
@@ -0,0 +1,554 @@
+//! Provisioning of the headless device via the Wi-Fi hotspot
+//!
+//! If there is no connection at startup, the device brings up its own
+//! WPA2 access point (the password is shown on the display) and serves a
+//! setup page (`http://10.42.0.1:8080/`) on it. A phone connected to the
+//! hotspot sends the Wi-Fi credentials, city and API key there. Then the
+//! hotspot is stopped and the device connects to the given network.
+
+use anyhow::{Result, anyhow};
+use std::{fs, io::Read, path::Path, time::Duration};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+use super::{
+    cities::CityDb,
+    network::{self, ConnectStatus, HOTSPOT_ADDR, Security},
+};
+use crate::{
+    api::geocoding,
+    config::Config,
+    i18n::{self, tr},
+};
+
+/// Default SSID of the hotspot
+pub const HOTSPOT_SSID: &str = "Ice-Setup";
+
+/// Default port of the setup page
+pub const PORT: u16 = 8080;
+
+/// Default time (in seconds) to wait for the connection at startup
+pub const WAIT_TIMEOUT: u64 = 60;
+
+/// Max size of the HTTP request (headers and body)
+const MAX_REQUEST_SIZE: usize = 16 * 1024;
+
+/// Max time to receive the HTTP request, so a stalled client doesn't block
+/// the others
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Characters of the generated hotspot password (without the similar
+/// looking `o`/`0` and `l`/`1`)
+const PASSWORD_CHARS: &[u8; 32] = b"abcdefghijkmnpqrstuvwxyz23456789";
+
+/// Length of the generated hotspot password
+const PASSWORD_LEN: usize = 10;
+
+/// Data entered on the setup page
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Credentials {
+    pub ssid: String,
+
+    /// Wi-Fi password (the network is open if it isn't set)
+    pub password: Option<String>,
+    pub city: Option<String>,
+
+    /// OpenWeatherMap API key
+    pub appid: Option<String>,
+}
+
+impl Credentials {
+    /// Parses the submitted form (`application/x-www-form-urlencoded`)
+    pub fn from_form(body: &[u8]) -> Result<Self> {
+        let mut creds = Self::default();
+        for (key, value) in form_urlencoded::parse(body) {
+            let value = value.trim().to_string();
+            let value = (!value.is_empty()).then_some(value);
+            match key.as_ref() {
+                "ssid" => creds.ssid = value.unwrap_or_default(),
+                "password" => creds.password = value,
+                "city" => creds.city = value,
+                "appid" => creds.appid = value,
+                _ => {}
+            }
+        }
+
+        if creds.ssid.is_empty() {
+            return Err(anyhow!(tr!("error-wifi-no-ssid")));
+        }
+        // The security of the network isn't known yet, so only the common
+        // limits are checked here
+        if let Some(pass) = &creds.password {
+            network::check_password(Some(pass), Security::Sae)?;
+        }
+        Ok(creds)
+    }
+
+    /// Saves the API key to the config
+    pub fn apply_appid(&self, conf: &mut Config) {
+        if let Some(appid) = &self.appid {
+            conf.appid = appid.clone();
+        }
+    }
+
+    /// Saves the coordinates of the city to the config. The city is
+    /// geocoded, so the device must be online (the bundled city database
+    /// is used otherwise)
+    pub async fn apply_city(&self, conf: &mut Config) -> Result<()> {
+        if let Some(city) = &self.city {
+            let online = geocoding::Location::get(&conf.api.geocoding, city, &conf.appid)
+                .await
+                .ok()
+                .and_then(|geo| geo.0.into_iter().next());
+            let offline = match &online {
+                Some(_) => None,
+                None => CityDb::bundled()
+                    .await
+                    .and_then(|db| db.search(city, 1).into_iter().next()),
+            };
+            let loc = online
+                .or(offline)
+                .ok_or_else(|| anyhow!(tr!("error-city-not-found", city = city.as_str())))?;
+            conf.coords = Some((loc.lat, loc.lon));
+        }
+        Ok(())
+    }
+}
+
+/// Result of [`run`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    /// The device is online, the config isn't changed
+    Online,
+
+    /// The config has been changed and must be saved. `warning` is the
+    /// non-fatal error (the city isn't found)
+    Provisioned { warning: Option<String> },
+}
+
+/// Stage of the provisioning
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    /// Waiting for the connection at startup
+    Waiting,
+
+    /// The hotspot is running and the setup page is available at `url`
+    Hotspot {
+        ssid: String,
+        password: String,
+        url: String,
+    },
+
+    /// Connecting to the network from the setup page
+    Connecting(String),
+}
+
+/// Replaces the HTML special characters
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Setup page with the optional error of the previous attempt
+fn page(error: Option<&str>) -> String {
+    let error = error
+        .map(|error| format!("<p class=\"error\">{}</p>", escape(error)))
+        .unwrap_or_default();
+    let lang = i18n::language().code();
+    let title = tr!("setup-title");
+    let ssid = tr!("setup-ssid");
+    let password = tr!("setup-password");
+    let city = tr!("setup-city");
+    let appid = tr!("setup-appid");
+    let save = tr!("setup-save");
+
+    format!(
+        r#"<!DOCTYPE html>
+<html lang="{lang}">
+<head>
+<meta charset="utf-8">
+<meta name="viewport" content="width=device-width, initial-scale=1">
+<title>{title}</title>
+<style>
+body {{ font-family: sans-serif; max-width: 30em; margin: 1em auto; padding: 0 1em; }}
+input {{ width: 100%; padding: .5em; margin: .2em 0 1em; box-sizing: border-box; }}
+button {{ width: 100%; padding: .7em; }}
+.error {{ color: #c00; }}
+</style>
+</head>
+<body>
+<h1>{title}</h1>
+{error}
+<form method="post" action="/">
+<label>{ssid}<input name="ssid" required></label>
+<label>{password}<input name="password" type="password"></label>
+<label>{city}<input name="city"></label>
+<label>{appid}<input name="appid"></label>
+<button type="submit">{save}</button>
+</form>
+</body>
+</html>
+"#
+    )
+}
+
+/// HTTP request to the setup page
+struct HttpRequest {
+    method: String,
+    body: Vec<u8>,
+}
+
+async fn read_request(stream: &mut TcpStream) -> Result<HttpRequest> {
+    let mut buf = Vec::new();
+    let mut chunk = [0; 4096];
+
+    loop {
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            return Err(anyhow!(tr!("error-request-closed")));
+        }
+        buf.extend_from_slice(&chunk[..read]);
+        if buf.len() > MAX_REQUEST_SIZE {
+            return Err(anyhow!(tr!("error-request-too-large")));
+        }
+
+        let mut headers = [httparse::EMPTY_HEADER; 32];
+        let mut req = httparse::Request::new(&mut headers);
+        let httparse::Status::Complete(header_len) = req.parse(&buf)? else {
+            continue;
+        };
+
+        let content_len = req
+            .headers
+            .iter()
+            .find(|header| header.name.eq_ignore_ascii_case("content-length"))
+            .and_then(|header| std::str::from_utf8(header.value).ok()?.trim().parse().ok())
+            .unwrap_or(0usize);
+        if header_len + content_len > MAX_REQUEST_SIZE {
+            return Err(anyhow!(tr!("error-request-too-large")));
+        }
+        if buf.len() >= header_len + content_len {
+            return Ok(HttpRequest {
+                method: req.method.unwrap_or_default().to_string(),
+                body: buf[header_len..header_len + content_len].to_vec(),
+            });
+        }
+    }
+}
+
+async fn respond(stream: &mut TcpStream, body: &str) -> Result<()> {
+    let response = format!(
+        "HTTP/1.1 200 OK\r\nContent-Type: text/html; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+    Ok(())
+}
+
+/// Serves the setup page until the correct form is submitted. `error` is
+/// shown on the page (e.g. the wrong password of the previous attempt)
+pub async fn serve(listener: &TcpListener, error: Option<&str>) -> Result<Credentials> {
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+        // Broken requests from one client mustn't stop the provisioning
+        let Ok(Ok(req)) = tokio::time::timeout(REQUEST_TIMEOUT, read_request(&mut stream)).await
+        else {
+            continue;
+        };
+
+        if req.method != "POST" {
+            let _ = respond(&mut stream, &page(error)).await;
+            continue;
+        }
+        match Credentials::from_form(&req.body) {
+            Ok(creds) => {
+                let _ = respond(
+                    &mut stream,
+                    &format!(
+                        "<!DOCTYPE html><html lang=\"{}\"><head><meta charset=\"utf-8\"></head>\
+                        <body><h1>{}</h1><p>{}</p></body></html>",
+                        i18n::language().code(),
+                        tr!("setup-done-title"),
+                        tr!("setup-done", ssid = escape(&creds.ssid))
+                    ),
+                )
+                .await;
+                return Ok(creds);
+            }
+            Err(why) => {
+                let _ = respond(&mut stream, &page(Some(&why.to_string()))).await;
+            }
+        }
+    }
+}
+
+/// Connects to the network from the setup page. Networks which aren't
+/// found by the scanning are treated as hidden ones. Their security is
+/// unknown, so WPA3 (SAE) is tried after the failed WPA2 attempt
+pub async fn connect(creds: &Credentials) -> Result<ConnectStatus> {
+    let pass = creds.password.as_deref();
+    let found = network::scan_wifi()
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .find(|ap| ap.ssid == creds.ssid);
+
+    match found {
+        Some(ap) => network::connect_wifi(&ap, pass).await,
+        None if pass.is_none() => network::connect_hidden(&creds.ssid, Security::Open, None).await,
+        None => {
+            let status = network::connect_hidden(&creds.ssid, Security::WpaPsk, pass).await?;
+            if status == ConnectStatus::Connected {
+                return Ok(status);
+            }
+            // WPA3-only networks reject WPA2, the failed profile is deleted.
+            // The first error is reported if SAE fails too
+            match network::connect_hidden(&creds.ssid, Security::Sae, pass).await {
+                Ok(ConnectStatus::Connected) => Ok(ConnectStatus::Connected),
+                _ => Ok(status),
+            }
+        }
+    }
+}
+
+/// Generates the password of the hotspot. It's shown on the display only,
+/// so the setup page isn't available to everyone in the radio range
+fn random_password() -> Result<String> {
+    let mut bytes = [0; PASSWORD_LEN];
+    fs::File::open("/dev/urandom")?.read_exact(&mut bytes)?;
+    Ok(bytes
+        .iter()
+        .map(|byte| PASSWORD_CHARS[*byte as usize % PASSWORD_CHARS.len()] as char)
+        .collect())
+}
+
+/// Opens the setup page on the hotspot address only, so it isn't
+/// available from the other networks of the device
+async fn bind(port: u16) -> Result<TcpListener> {
+    TcpListener::bind((HOTSPOT_ADDR, port))
+        .await
+        .map_err(|err| {
+            anyhow!(tr!(
+                "error-setup-page",
+                port = port,
+                error = err.to_string()
+            ))
+        })
+}
+
+/// Runs the provisioning if there is no connection at startup. Stages are
+/// passed to `report`
+pub async fn run(conf: &mut Config, mut report: impl FnMut(Status)) -> Result<Outcome> {
+    let settings = conf.provisioning.clone();
+    if !settings.enabled {
+        return Ok(Outcome::Online);
+    }
+
+    report(Status::Waiting);
+    // Nothing to do without NetworkManager
+    let timeout = Duration::from_secs(settings.timeout);
+    if network::wait_connected(timeout).await.unwrap_or(true) {
+        return Ok(Outcome::Online);
+    }
+
+    let password = match settings.password {
+        Some(password) => password,
+        None => random_password()?,
+    };
+    let url = match settings.port {
+        80 => format!("http://{HOTSPOT_ADDR}/"),
+        port => format!("http://{HOTSPOT_ADDR}:{port}/"),
+    };
+
+    let mut error = None;
+    let creds = loop {
+        let hotspot = network::start_hotspot(&settings.ssid, Some(&password)).await?;
+        report(Status::Hotspot {
+            ssid: settings.ssid.clone(),
+            password: password.clone(),
+            url: url.clone(),
+        });
+
+        // The hotspot address exists only while the hotspot is running
+        let creds = match bind(settings.port).await {
+            Ok(listener) => serve(&listener, error.as_deref()).await,
+            Err(why) => Err(why),
+        };
+        network::stop_hotspot(&hotspot).await?;
+        let creds = creds?;
+
+        report(Status::Connecting(creds.ssid.clone()));
+        error = Some(match connect(&creds).await {
+            Ok(ConnectStatus::Connected) => break creds,
+            Ok(ConnectStatus::WrongPassword) => {
+                tr!("setup-wrong-password", ssid = creds.ssid.as_str())
+            }
+            Ok(ConnectStatus::Timeout) => {
+                tr!("setup-timeout", ssid = creds.ssid.as_str())
+            }
+            Ok(ConnectStatus::Failed(reason)) => {
+                tr!("setup-failed", ssid = creds.ssid.as_str(), reason = reason)
+            }
+            Err(why) => why.to_string(),
+        });
+    };
+
+    // The device is online now and the provisioning won't run again, so
+    // the API key is saved even if the city isn't found
+    creds.apply_appid(conf);
+    let warning = creds
+        .apply_city(conf)
+        .await
+        .err()
+        .map(|why| why.to_string());
+    Ok(Outcome::Provisioned { warning })
+}
+
+/// Writes the API key and the location changed by [`run`] (`provisioned`
+/// against `original`) into the config at `path`. The config is read again,
+/// so the settings saved during the provisioning are kept. A missing config
+/// (the first start) is created
+pub fn save(original: &Config, provisioned: &Config, path: &Path) -> Result<()> {
+    let mut conf = if path.exists() {
+        Config::read(path)?
+    } else {
+        Config::default()
+    };
+    if provisioned.appid != original.appid {
+        conf.appid = provisioned.appid.clone();
+    }
+    if provisioned.coords != original.coords {
+        conf.coords = provisioned.coords;
+    }
+    conf.write(path)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn form_test() {
+        let creds = Credentials::from_form(
+            b"ssid=My+Home&password=correct%20horse&city=%D0%94%D0%B7%D0%B5%D1%80%D0%B6%D0%B8%D0%BD%D1%81%D0%BA&appid=",
+        )
+        .unwrap();
+        assert_eq!(
+            creds,
+            Credentials {
+                ssid: "My Home".to_string(),
+                password: Some("correct horse".to_string()),
+                city: Some("Дзержинск".to_string()),
+                appid: None,
+            }
+        );
+
+        assert!(Credentials::from_form(b"ssid=&password=12345678").is_err());
+        assert!(Credentials::from_form(b"ssid=Home&password=short").is_err());
+    }
+
+    #[tokio::test]
+    async fn apply_test() {
+        let mut conf = Config::default();
+        conf.api.geocoding = crate::api::test::serve_json("[]").await;
+        let creds = Credentials {
+            ssid: "Home".to_string(),
+            password: None,
+            city: Some("Nowhere at all".to_string()),
+            appid: Some("0123456789abcdef0123456789abcdef".to_string()),
+        };
+
+        // The API key is kept when the city isn't found
+        creds.apply_appid(&mut conf);
+        assert!(creds.apply_city(&mut conf).await.is_err());
+        assert_eq!(conf.appid, "0123456789abcdef0123456789abcdef");
+        assert_eq!(conf.coords, None);
+    }
+
+    #[test]
+    fn save_test() {
+        let dir = std::env::temp_dir().join("ice-test-provision");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("ice.toml");
+
+        let original = Config::default();
+        let provisioned = Config {
+            coords: Some((56.24, 43.46)),
+            ..Config::default()
+        };
+
+        // Missing config is created
+        save(&original, &provisioned, &path).unwrap();
+        assert_eq!(Config::read(&path).unwrap().coords, Some((56.24, 43.46)));
+
+        // The settings saved during the provisioning are kept
+        let mut saved = Config::read(&path).unwrap();
+        saved.refresh_interval = 60;
+        saved.write(&path).unwrap();
+        let provisioned = Config {
+            appid: "0123456789abcdef0123456789abcdef".to_string(),
+            ..original.clone()
+        };
+        save(&original, &provisioned, &path).unwrap();
+        let conf = Config::read(&path).unwrap();
+        assert_eq!(conf.refresh_interval, 60);
+        assert_eq!(conf.coords, Some((56.24, 43.46)));
+        assert_eq!(conf.appid, provisioned.appid);
+
+        // The broken config isn't replaced
+        fs::write(&path, "units = ").unwrap();
+        assert!(save(&original, &provisioned, &path).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "units = ");
+    }
+
+    #[test]
+    fn random_password_test() {
+        let password = random_password().unwrap();
+        assert_eq!(password.len(), PASSWORD_LEN);
+        assert!(password.bytes().all(|c| PASSWORD_CHARS.contains(&c)));
+        assert!(network::check_password(Some(&password), Security::WpaPsk).is_ok());
+        assert_ne!(password, random_password().unwrap());
+    }
+
+    #[tokio::test]
+    async fn serve_test() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        let client = tokio::spawn(async move {
+            let request = async |req: String| {
+                let mut stream = TcpStream::connect(addr).await.unwrap();
+                stream.write_all(req.as_bytes()).await.unwrap();
+                let mut resp = String::new();
+                stream.read_to_string(&mut resp).await.unwrap();
+                resp
+            };
+            let post = |body: &str| {
+                format!(
+                    "POST / HTTP/1.1\r\nHost: {HOTSPOT_ADDR}\r\nContent-Type: application/x-www-form-urlencoded\r\nContent-Length: {}\r\n\r\n{body}",
+                    body.len()
+                )
+            };
+
+            let form = request("GET / HTTP/1.1\r\nHost: 10.42.0.1\r\n\r\n".to_string()).await;
+            let invalid = request(post("ssid=")).await;
+            let done = request(post("ssid=Office&password=correct+horse")).await;
+            (form, invalid, done)
+        });
+
+        let creds = serve(&listener, Some("Неверный пароль")).await.unwrap();
+        let (form, invalid, done) = client.await.unwrap();
+
+        assert!(form.contains("<form") && form.contains("Неверный пароль"));
+        assert!(invalid.contains(&tr!("error-wifi-no-ssid")));
+        assert!(done.contains("Office"));
+        assert_eq!(creds.ssid, "Office");
+        assert_eq!(creds.password.as_deref(), Some("correct horse"));
+    }
+}
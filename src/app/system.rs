@@ -1 +1,742 @@
-//! CPU, RAM and disk space monitoring
+//! CPU, RAM and disk space monitoring and the device information
+//!
+//! Everything except the disk space and IP addresses is read from `/proc`,
+//! `/sys` and `/etc/os-release`. Their paths are set in [`System`], so the
+//! readers can be tested on a fake tree.
+
+use anyhow::{Result, anyhow};
+use std::{
+    ffi::{CStr, CString},
+    fs,
+    mem::MaybeUninit,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    os::unix::ffi::OsStrExt,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::i18n::tr;
+
+/// Time spent by the CPU in different states (in `USER_HZ` ticks)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct CpuTimes {
+    pub user: u64,
+    pub nice: u64,
+    pub system: u64,
+    pub idle: u64,
+    pub iowait: u64,
+    pub irq: u64,
+    pub softirq: u64,
+    pub steal: u64,
+}
+
+impl CpuTimes {
+    /// Parses the `cpu` line of the `/proc/stat` (without the name)
+    fn parse(fields: &str) -> Option<Self> {
+        let mut fields = fields.split_whitespace().map(|field| field.parse::<u64>());
+        let mut next = || fields.next().unwrap_or(Ok(0)).ok();
+
+        Some(Self {
+            user: next()?,
+            nice: next()?,
+            system: next()?,
+            idle: next()?,
+            iowait: next()?,
+            irq: next()?,
+            softirq: next()?,
+            steal: next()?,
+        })
+    }
+
+    fn idle(&self) -> u64 {
+        self.idle + self.iowait
+    }
+
+    fn total(&self) -> u64 {
+        self.user + self.nice + self.system + self.irq + self.softirq + self.steal + self.idle()
+    }
+
+    /// Returns the CPU load (in percent) between `prev` and `self`
+    pub fn load_since(&self, prev: &Self) -> f32 {
+        let total = self.total().saturating_sub(prev.total());
+        let idle = self.idle().saturating_sub(prev.idle());
+        if total == 0 {
+            return 0.;
+        }
+        (total - idle.min(total)) as f32 * 100. / total as f32
+    }
+}
+
+/// CPU statistics from the `/proc/stat`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CpuStat {
+    /// All cores together
+    pub total: CpuTimes,
+    pub cores: Vec<CpuTimes>,
+}
+
+impl CpuStat {
+    pub fn parse(stat: &str) -> Result<Self> {
+        let mut cpu = Self::default();
+        let mut has_total = false;
+
+        for line in stat.lines() {
+            let Some((name, fields)) = line.split_once(char::is_whitespace) else {
+                continue;
+            };
+            if name == "cpu" {
+                cpu.total = CpuTimes::parse(fields)
+                    .ok_or_else(|| anyhow!(tr!("error-proc-stat-line", line = line)))?;
+                has_total = true;
+            } else if name.starts_with("cpu") {
+                cpu.cores.push(
+                    CpuTimes::parse(fields)
+                        .ok_or_else(|| anyhow!(tr!("error-proc-stat-line", line = line)))?,
+                );
+            }
+        }
+
+        if !has_total {
+            return Err(anyhow!(tr!("error-proc-stat")));
+        }
+        Ok(cpu)
+    }
+
+    /// Returns the load of all cores and of each core (in percent) between
+    /// `prev` and `self`
+    pub fn load_since(&self, prev: &Self) -> (f32, Vec<f32>) {
+        (
+            self.total.load_since(&prev.total),
+            self.cores
+                .iter()
+                .zip(&prev.cores)
+                .map(|(core, prev)| core.load_since(prev))
+                .collect(),
+        )
+    }
+}
+
+/// RAM and swap usage (in bytes) from the `/proc/meminfo`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Memory {
+    pub total: u64,
+    pub free: u64,
+
+    /// Memory available for new programs without swapping (including the
+    /// caches)
+    pub available: u64,
+    pub swap_total: u64,
+    pub swap_free: u64,
+}
+
+impl Memory {
+    pub fn parse(meminfo: &str) -> Result<Self> {
+        let mut mem = Self::default();
+        let mut has_available = false;
+
+        for line in meminfo.lines() {
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            // Values are in kB
+            let Some(value) = value
+                .split_whitespace()
+                .next()
+                .and_then(|value| value.parse::<u64>().ok())
+                .map(|kb| kb * 1024)
+            else {
+                continue;
+            };
+
+            match key {
+                "MemTotal" => mem.total = value,
+                "MemFree" => mem.free = value,
+                "MemAvailable" => {
+                    mem.available = value;
+                    has_available = true;
+                }
+                "SwapTotal" => mem.swap_total = value,
+                "SwapFree" => mem.swap_free = value,
+                _ => {}
+            }
+        }
+
+        if mem.total == 0 {
+            return Err(anyhow!(tr!("error-proc-meminfo")));
+        }
+        // Kernels older than 3.14
+        if !has_available {
+            mem.available = mem.free;
+        }
+        Ok(mem)
+    }
+
+    pub fn used(&self) -> u64 {
+        self.total.saturating_sub(self.available)
+    }
+
+    /// Returns the RAM usage (in percent)
+    pub fn usage(&self) -> f32 {
+        self.used() as f32 * 100. / self.total as f32
+    }
+
+    pub fn swap_used(&self) -> u64 {
+        self.swap_total.saturating_sub(self.swap_free)
+    }
+}
+
+/// Space on the mounted filesystem (in bytes)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Disk {
+    pub mount: PathBuf,
+    pub total: u64,
+    pub free: u64,
+
+    /// Free space available for unprivileged users
+    pub available: u64,
+}
+
+impl Disk {
+    /// Returns the space on the filesystem which `mount` belongs to
+    pub fn get<P: AsRef<Path>>(mount: P) -> Result<Self> {
+        let mount = mount.as_ref();
+        let path = CString::new(mount.as_os_str().as_bytes())?;
+        let mut stat = MaybeUninit::<libc::statvfs>::uninit();
+
+        // SAFETY: `path` is a valid C string and `stat` is written by the
+        // successful call only
+        let stat = unsafe {
+            if libc::statvfs(path.as_ptr(), stat.as_mut_ptr()) != 0 {
+                return Err(anyhow!(tr!(
+                    "error-disk",
+                    mount = mount.display().to_string(),
+                    error = std::io::Error::last_os_error().to_string()
+                )));
+            }
+            stat.assume_init()
+        };
+
+        let block = stat.f_frsize as u64;
+        Ok(Self {
+            mount: mount.to_path_buf(),
+            total: stat.f_blocks as u64 * block,
+            free: stat.f_bfree as u64 * block,
+            available: stat.f_bavail as u64 * block,
+        })
+    }
+
+    pub fn used(&self) -> u64 {
+        self.total.saturating_sub(self.free)
+    }
+
+    /// Returns the disk usage (in percent) as `df` does
+    pub fn usage(&self) -> f32 {
+        let size = self.used() + self.available;
+        if size == 0 {
+            return 0.;
+        }
+        self.used() as f32 * 100. / size as f32
+    }
+}
+
+/// Temperature sensor from the `/sys/class/thermal`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Thermal {
+    /// Type of the thermal zone (`cpu-thermal`, `soc_thermal`...)
+    pub kind: String,
+
+    /// Temperature (in °C)
+    pub temp: f32,
+}
+
+/// CPU frequency and throttling state
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Throttling {
+    /// Current frequency of the first core (in kHz)
+    pub cur_freq: Option<u32>,
+
+    /// State of the CPU cooling device. It's non-zero while the thermal
+    /// framework lowers the frequency of the overheated SoC. A static
+    /// `scaling_max_freq` cap set by the user doesn't change it
+    pub cooling_state: Option<u32>,
+
+    /// Flags of the Raspberry Pi firmware (`vcgencmd get_throttled`)
+    pub flags: Option<u32>,
+}
+
+impl Throttling {
+    /// Under-voltage is detected now
+    const UNDER_VOLTAGE: u32 = 0x1;
+
+    /// ARM frequency is capped now
+    const FREQ_CAPPED: u32 = 0x2;
+
+    /// CPU is throttled now
+    const THROTTLED: u32 = 0x4;
+
+    /// Under-voltage has occurred since the boot
+    const UNDER_VOLTAGE_OCCURRED: u32 = 0x10000;
+
+    pub fn under_voltage(&self) -> bool {
+        self.flags
+            .is_some_and(|flags| flags & Self::UNDER_VOLTAGE != 0)
+    }
+
+    pub fn under_voltage_occurred(&self) -> bool {
+        self.flags
+            .is_some_and(|flags| flags & Self::UNDER_VOLTAGE_OCCURRED != 0)
+    }
+
+    /// CPU frequency is lowered because of the overheating or the power
+    /// supply problems. The firmware flags are trusted when available,
+    /// otherwise the state of the cpufreq cooling device is checked
+    pub fn is_throttled(&self) -> bool {
+        match self.flags {
+            Some(flags) => flags & (Self::FREQ_CAPPED | Self::THROTTLED) != 0,
+            None => self.cooling_state.is_some_and(|state| state > 0),
+        }
+    }
+}
+
+/// All readings at once
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub cpu: CpuStat,
+    pub memory: Memory,
+    pub disks: Vec<Disk>,
+    pub temperature: Option<Thermal>,
+    pub throttling: Throttling,
+    pub uptime: Duration,
+}
+
+/// Address of the network interface
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Address {
+    pub interface: String,
+    pub ip: IpAddr,
+}
+
+/// Returns addresses of all interfaces except the loopback and the IPv6
+/// link-local ones
+pub fn addresses() -> Result<Vec<Address>> {
+    let mut ifaddrs = std::ptr::null_mut();
+    // SAFETY: the list is freed below and isn't used after that
+    if unsafe { libc::getifaddrs(&mut ifaddrs) } != 0 {
+        return Err(anyhow!(tr!(
+            "error-addresses",
+            error = std::io::Error::last_os_error().to_string()
+        )));
+    }
+
+    let mut addresses = vec![];
+    let mut next = ifaddrs;
+    while !next.is_null() {
+        // SAFETY: `next` points to the list item, `ifa_addr` is checked and
+        // its type is defined by `sa_family`
+        let (ifaddr, ip) = unsafe {
+            let ifaddr = &*next;
+            next = ifaddr.ifa_next;
+            if ifaddr.ifa_addr.is_null() {
+                continue;
+            }
+
+            let ip = match i32::from((*ifaddr.ifa_addr).sa_family) {
+                libc::AF_INET => {
+                    let addr = &*(ifaddr.ifa_addr as *const libc::sockaddr_in);
+                    IpAddr::V4(Ipv4Addr::from(u32::from_be(addr.sin_addr.s_addr)))
+                }
+                libc::AF_INET6 => {
+                    let addr = &*(ifaddr.ifa_addr as *const libc::sockaddr_in6);
+                    IpAddr::V6(Ipv6Addr::from(addr.sin6_addr.s6_addr))
+                }
+                _ => continue,
+            };
+            (ifaddr, ip)
+        };
+
+        let link_local = matches!(ip, IpAddr::V6(ip) if ip.segments()[0] & 0xffc0 == 0xfe80);
+        if ip.is_loopback() || link_local {
+            continue;
+        }
+        addresses.push(Address {
+            // SAFETY: the name is the valid C string
+            interface: unsafe { CStr::from_ptr(ifaddr.ifa_name) }
+                .to_string_lossy()
+                .into_owned(),
+            ip,
+        });
+    }
+
+    // SAFETY: `ifaddrs` is returned by the successful `getifaddrs` call
+    unsafe { libc::freeifaddrs(ifaddrs) };
+    Ok(addresses)
+}
+
+/// Information about the device for the support
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Info {
+    /// Model of the board (`Raspberry Pi 4 Model B Rev 1.4`)
+    pub model: Option<String>,
+
+    /// Name of the distribution (`Armbian 24.5.1 bookworm`)
+    pub os: Option<String>,
+    pub kernel: Option<String>,
+    pub addresses: Vec<Address>,
+}
+
+/// Reader of the system state
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct System {
+    pub proc: PathBuf,
+    pub sys: PathBuf,
+
+    /// Path to the `os-release` file
+    pub os_release: PathBuf,
+}
+
+impl Default for System {
+    fn default() -> Self {
+        Self::new("/proc", "/sys")
+    }
+}
+
+impl System {
+    pub fn new<P: Into<PathBuf>, S: Into<PathBuf>>(proc: P, sys: S) -> Self {
+        Self {
+            proc: proc.into(),
+            sys: sys.into(),
+            os_release: PathBuf::from("/etc/os-release"),
+        }
+    }
+
+    /// Reads the string attribute. Device tree strings end with `\0`
+    fn read_str(path: &Path) -> Option<String> {
+        let value = fs::read_to_string(path).ok()?;
+        let value = value.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+        (!value.is_empty()).then(|| value.to_string())
+    }
+
+    /// Returns the model of the board from the device tree (or from the DMI
+    /// on PCs)
+    pub fn model(&self) -> Option<String> {
+        Self::read_str(&self.proc.join("device-tree/model"))
+            .or_else(|| Self::read_str(&self.sys.join("class/dmi/id/product_name")))
+    }
+
+    pub fn kernel(&self) -> Option<String> {
+        Self::read_str(&self.proc.join("sys/kernel/osrelease"))
+    }
+
+    /// Returns `PRETTY_NAME` (or `NAME`) from the `os-release`
+    pub fn os(&self) -> Option<String> {
+        let os_release = fs::read_to_string(&self.os_release).ok()?;
+        let value = |key: &str| {
+            os_release.lines().find_map(|line| {
+                let value = line.strip_prefix(key)?.strip_prefix('=')?;
+                Some(value.trim().trim_matches(['"', '\'']).to_string())
+            })
+        };
+        value("PRETTY_NAME").or_else(|| value("NAME"))
+    }
+
+    /// Collects the information about the device. Unavailable values are
+    /// skipped
+    pub fn info(&self) -> Info {
+        Info {
+            model: self.model(),
+            os: self.os(),
+            kernel: self.kernel(),
+            addresses: addresses().unwrap_or_default(),
+        }
+    }
+
+    fn read(path: &Path) -> Result<String> {
+        fs::read_to_string(path).map_err(|err| {
+            anyhow!(tr!(
+                "error-read",
+                path = path.display().to_string(),
+                error = err.to_string()
+            ))
+        })
+    }
+
+    /// Reads the number from the `sysfs` attribute
+    fn read_num<T: std::str::FromStr>(path: &Path) -> Option<T> {
+        fs::read_to_string(path).ok()?.trim().parse().ok()
+    }
+
+    pub fn cpu(&self) -> Result<CpuStat> {
+        CpuStat::parse(&Self::read(&self.proc.join("stat"))?)
+    }
+
+    pub fn memory(&self) -> Result<Memory> {
+        Memory::parse(&Self::read(&self.proc.join("meminfo"))?)
+    }
+
+    pub fn uptime(&self) -> Result<Duration> {
+        let uptime = Self::read(&self.proc.join("uptime"))?;
+        uptime
+            .split_whitespace()
+            .next()
+            .and_then(|secs| secs.parse::<f64>().ok())
+            .map(Duration::from_secs_f64)
+            .ok_or_else(|| anyhow!(tr!("error-proc-uptime", uptime = uptime)))
+    }
+
+    /// Returns all thermal zones
+    pub fn thermal_zones(&self) -> Result<Vec<Thermal>> {
+        let dir = self.sys.join("class/thermal");
+        let mut zones = vec![];
+
+        let mut entries = fs::read_dir(&dir)
+            .map_err(|err| {
+                anyhow!(tr!(
+                    "error-read",
+                    path = dir.display().to_string(),
+                    error = err.to_string()
+                ))
+            })?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                entry
+                    .file_name()
+                    .to_string_lossy()
+                    .starts_with("thermal_zone")
+            })
+            .map(|entry| entry.path())
+            .collect::<Vec<_>>();
+        entries.sort();
+
+        for zone in entries {
+            // Values are in millidegrees
+            let Some(temp) = Self::read_num::<i64>(&zone.join("temp")) else {
+                continue;
+            };
+            zones.push(Thermal {
+                kind: fs::read_to_string(zone.join("type"))
+                    .map(|kind| kind.trim().to_string())
+                    .unwrap_or_default(),
+                temp: temp as f32 / 1000.,
+            });
+        }
+        Ok(zones)
+    }
+
+    /// Returns the temperature of the SoC: the CPU zone or the first one
+    pub fn soc_temperature(&self) -> Result<Option<Thermal>> {
+        let zones = self.thermal_zones()?;
+        let soc = zones
+            .iter()
+            .position(|zone| {
+                let kind = zone.kind.to_lowercase();
+                kind.contains("cpu") || kind.contains("soc")
+            })
+            .unwrap_or(0);
+        Ok(zones.into_iter().nth(soc))
+    }
+
+    pub fn throttling(&self) -> Throttling {
+        let cpufreq = self.sys.join("devices/system/cpu/cpu0/cpufreq");
+        Throttling {
+            cur_freq: Self::read_num(&cpufreq.join("scaling_cur_freq")),
+            cooling_state: self.cooling_state(),
+            flags: fs::read_to_string(
+                self.sys
+                    .join("devices/platform/soc/soc:firmware/get_throttled"),
+            )
+            .ok()
+            .and_then(|flags| u32::from_str_radix(flags.trim().trim_start_matches("0x"), 16).ok()),
+        }
+    }
+
+    /// State of the first cpufreq cooling device (`cpufreq-cpu0` or
+    /// `cpufreq-0` depending on the kernel)
+    fn cooling_state(&self) -> Option<u32> {
+        fs::read_dir(self.sys.join("class/thermal"))
+            .ok()?
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| {
+                fs::read_to_string(path.join("type"))
+                    .is_ok_and(|kind| kind.trim().starts_with("cpufreq"))
+            })
+            .find_map(|path| Self::read_num(&path.join("cur_state")))
+    }
+
+    /// Reads everything. Disk space is returned for the available `mounts`
+    /// only; missing thermal zones aren't an error
+    pub fn snapshot<P: AsRef<Path>>(&self, mounts: &[P]) -> Result<Snapshot> {
+        Ok(Snapshot {
+            cpu: self.cpu()?,
+            memory: self.memory()?,
+            disks: mounts
+                .iter()
+                .filter_map(|mount| Disk::get(mount).ok())
+                .collect(),
+            temperature: self.soc_temperature().ok().flatten(),
+            throttling: self.throttling(),
+            uptime: self.uptime()?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Creates the fake `/proc` and `/sys` trees in the temp directory
+    fn fake_system(name: &str, files: &[(&str, &str)]) -> System {
+        let root = std::env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&root);
+        for (path, contents) in files {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        System::new(root.join("proc"), root.join("sys"))
+    }
+
+    #[test]
+    fn proc_test() {
+        let system = fake_system(
+            "ice-test-proc",
+            &[
+                (
+                    "proc/stat",
+                    "cpu  100 0 100 700 100 0 0 0 0 0\n\
+                     cpu0 50 0 50 350 50 0 0 0 0 0\n\
+                     cpu1 50 0 50 350 50 0 0 0 0 0\n\
+                     intr 12345\n",
+                ),
+                (
+                    "proc/meminfo",
+                    "MemTotal:        1000000 kB\n\
+                     MemFree:          200000 kB\n\
+                     MemAvailable:     750000 kB\n\
+                     SwapTotal:        500000 kB\n\
+                     SwapFree:         400000 kB\n",
+                ),
+                ("proc/uptime", "3725.42 7000.10\n"),
+            ],
+        );
+
+        let prev = system.cpu().unwrap();
+        assert_eq!(prev.cores.len(), 2);
+        let next = CpuStat::parse(
+            "cpu  250 0 150 800 100 0 0 0 0 0\n\
+             cpu0 150 0 100 400 50 0 0 0 0 0\n\
+             cpu1 100 0 50 400 50 0 0 0 0 0\n",
+        )
+        .unwrap();
+        let (total, cores) = next.load_since(&prev);
+        assert_eq!(total, 66.666664);
+        assert_eq!(cores, vec![75., 50.]);
+
+        let mem = system.memory().unwrap();
+        assert_eq!(mem.total, 1_024_000_000);
+        assert_eq!(mem.usage(), 25.);
+        assert_eq!(mem.swap_used(), 102_400_000);
+
+        assert_eq!(system.uptime().unwrap().as_secs(), 3725);
+        assert!(Memory::parse("MemFree: 1 kB").is_err());
+    }
+
+    #[test]
+    fn sys_test() {
+        let system = fake_system(
+            "ice-test-sys",
+            &[
+                ("sys/class/thermal/thermal_zone0/type", "gpu-thermal\n"),
+                ("sys/class/thermal/thermal_zone0/temp", "48500\n"),
+                ("sys/class/thermal/thermal_zone1/type", "cpu-thermal\n"),
+                ("sys/class/thermal/thermal_zone1/temp", "61250\n"),
+                (
+                    "sys/devices/system/cpu/cpu0/cpufreq/scaling_cur_freq",
+                    "1200000\n",
+                ),
+                (
+                    "sys/devices/system/cpu/cpu0/cpufreq/scaling_max_freq",
+                    "1200000\n",
+                ),
+                (
+                    "sys/devices/system/cpu/cpu0/cpufreq/cpuinfo_max_freq",
+                    "1800000\n",
+                ),
+                (
+                    "sys/devices/platform/soc/soc:firmware/get_throttled",
+                    "0x50000\n",
+                ),
+            ],
+        );
+
+        assert_eq!(system.thermal_zones().unwrap().len(), 2);
+        let soc = system.soc_temperature().unwrap().unwrap();
+        assert_eq!((soc.kind.as_str(), soc.temp), ("cpu-thermal", 61.25));
+
+        // The static cap of `scaling_max_freq` isn't throttling
+        let throttling = system.throttling();
+        assert!(!throttling.is_throttled());
+        assert!(!throttling.under_voltage());
+        assert!(throttling.under_voltage_occurred());
+        assert!(!Throttling::default().is_throttled());
+
+        let throttled = Throttling {
+            flags: Some(0x50004),
+            ..throttling
+        };
+        assert!(throttled.is_throttled());
+
+        // No firmware: the cooling device is checked
+        let system = fake_system(
+            "ice-test-cooling",
+            &[
+                ("sys/class/thermal/cooling_device0/type", "cpufreq-cpu0\n"),
+                ("sys/class/thermal/cooling_device0/cur_state", "0\n"),
+            ],
+        );
+        assert!(!system.throttling().is_throttled());
+        fs::write(
+            system.sys.join("class/thermal/cooling_device0/cur_state"),
+            "2\n",
+        )
+        .unwrap();
+        assert!(system.throttling().is_throttled());
+    }
+
+    #[test]
+    fn info_test() {
+        let mut system = fake_system(
+            "ice-test-info",
+            &[
+                ("proc/device-tree/model", "Raspberry Pi 4 Model B Rev 1.4\0"),
+                ("proc/sys/kernel/osrelease", "6.6.31-current-bcm2711\n"),
+                (
+                    "etc/os-release",
+                    "NAME=\"Armbian\"\nPRETTY_NAME=\"Armbian 24.5.1 bookworm\"\n",
+                ),
+            ],
+        );
+        system.os_release = std::env::temp_dir().join("ice-test-info/etc/os-release");
+
+        let info = system.info();
+        assert_eq!(
+            info.model.as_deref(),
+            Some("Raspberry Pi 4 Model B Rev 1.4")
+        );
+        assert_eq!(info.kernel.as_deref(), Some("6.6.31-current-bcm2711"));
+        assert_eq!(info.os.as_deref(), Some("Armbian 24.5.1 bookworm"));
+        assert!(info.addresses.iter().all(|addr| !addr.ip.is_loopback()));
+
+        system.os_release = PathBuf::from("/nonexistent");
+        assert_eq!(system.os(), None);
+    }
+
+    #[test]
+    fn disk_test() {
+        let disk = Disk::get(std::env::temp_dir()).unwrap();
+        assert!(disk.total > 0 && disk.free <= disk.total);
+        assert!((0. ..=100.).contains(&disk.usage()));
+
+        assert!(Disk::get("/nonexistent").is_err());
+    }
+}
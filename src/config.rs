@@ -2,49 +2,393 @@
 
 use anyhow::{Result, anyhow};
 use serde::{Deserialize, Serialize};
-use std::{fs, path::Path};
+use std::{
+    ffi::OsString,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
 use toml;
 
-use crate::units::Units;
+use crate::{
+    api::{current, daily, geocoding},
+    app::{geoip, gps, provision},
+    i18n::tr,
+    time::TimeFormat,
+    units::Units,
+};
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
 pub struct Config {
     pub coords: Option<(f32, f32)>,
     pub units: Units,
     pub appid: String,
     pub autodetect_location: bool,
+
+    /// Interval (in minutes) between the weather updates
+    #[serde(default = "Config::default_refresh_interval")]
+    pub refresh_interval: u64,
+
+    #[serde(default)]
+    pub interface: Interface,
+
+    #[serde(default)]
+    pub api: ApiUrls,
+
+    #[serde(default)]
+    pub gps: Gps,
+
+    /// IP geolocation providers in order of use
+    #[serde(default = "geoip::Provider::defaults")]
+    pub geoip: Vec<geoip::Provider>,
+
+    #[serde(default)]
+    pub provisioning: Provisioning,
+
+    #[serde(default)]
+    pub system: SystemMonitor,
+}
+
+/// Color theme of the interface
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ThemeMode {
+    /// Light in the daytime, dark at night
+    #[default]
+    Auto,
+    Light,
+    Dark,
+}
+
+/// Language of the interface. Configs without the `language` key are
+/// older than the translations, so they stay Russian: the locale of
+/// systemd services is often unset. The bundled config uses `auto`
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Language {
+    /// Detected from the locale (`LANG`)
+    Auto,
+    #[default]
+    Ru,
+    En,
+}
+
+/// Appearance of the interface
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(default)]
+pub struct Interface {
+    pub theme: ThemeMode,
+    pub time_format: TimeFormat,
+    pub language: Language,
+}
+
+/// Settings of the system monitor
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct SystemMonitor {
+    /// Mount points to show the free disk space of
+    pub mounts: Vec<PathBuf>,
+}
+
+impl Default for SystemMonitor {
+    fn default() -> Self {
+        Self {
+            mounts: vec![PathBuf::from("/")],
+        }
+    }
+}
+
+/// Setup via the Wi-Fi hotspot when there is no connection at startup
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct Provisioning {
+    pub enabled: bool,
+    pub ssid: String,
+
+    /// Password of the hotspot (8-63 characters). If it isn't set, the
+    /// random one is generated at every start and shown on the display
+    pub password: Option<String>,
+
+    /// Port of the setup page
+    pub port: u16,
+
+    /// Time (in seconds) to wait for the connection at startup
+    pub timeout: u64,
+}
+
+impl Default for Provisioning {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ssid: provision::HOTSPOT_SSID.to_string(),
+            password: None,
+            port: provision::PORT,
+            timeout: provision::WAIT_TIMEOUT,
+        }
+    }
+}
+
+/// Settings of the GPS receiver
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct Gps {
+    /// Address of the `gpsd`
+    pub addr: String,
+
+    /// Time (in seconds) to wait for the fix
+    pub timeout: u64,
+}
+
+impl Default for Gps {
+    fn default() -> Self {
+        Self {
+            addr: gps::GPSD_ADDR.to_string(),
+            timeout: gps::FIX_TIMEOUT,
+        }
+    }
+}
+
+/// Base URLs of all endpoints used by the Ice
+///
+/// Can be overridden to route requests through the caching proxy or to
+/// point the program at a local test server.
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct ApiUrls {
+    pub current: String,
+    pub daily: String,
+    pub geocoding: String,
+    pub reverse_geocoding: String,
+    pub zip_geocoding: String,
+
+    /// URL of the ip-api.com from the previous versions. Replaced by the
+    /// `[[geoip]]` list and moved there by [`Config::read`]
+    #[serde(skip_serializing)]
+    pub ip_location: Option<String>,
+}
+
+impl Default for ApiUrls {
+    fn default() -> Self {
+        Self {
+            current: current::API_URL.to_string(),
+            daily: daily::API_URL.to_string(),
+            geocoding: geocoding::API_URL.to_string(),
+            reverse_geocoding: geocoding::REVERSE_API_URL.to_string(),
+            zip_geocoding: geocoding::ZIP_API_URL.to_string(),
+            ip_location: None,
+        }
+    }
 }
 
 impl Default for Config {
     fn default() -> Self {
         Self {
-            coords: Some((56.3287, 44.002)),
+            coords: None,
             units: Units::default(),
             appid: "26896f0fe821b98790eeae3a316f3358".to_string(),
             autodetect_location: true,
+            refresh_interval: Self::default_refresh_interval(),
+            interface: Interface::default(),
+            api: ApiUrls::default(),
+            gps: Gps::default(),
+            geoip: geoip::Provider::defaults(),
+            provisioning: Provisioning::default(),
+            system: SystemMonitor::default(),
         }
     }
 }
 
 impl Config {
+    /// Allowed weather update intervals (in minutes)
+    pub const REFRESH_INTERVALS: std::ops::RangeInclusive<u64> = 5..=24 * 60;
+
+    fn default_refresh_interval() -> u64 {
+        30
+    }
+
+    /// Checks the values which can be changed in the settings
+    pub fn validate(&self) -> Result<()> {
+        if self.appid.len() != 32 || !self.appid.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(anyhow!(tr!("error-appid")));
+        }
+        if !self.autodetect_location && self.coords.is_none() {
+            return Err(anyhow!(tr!("error-no-location")));
+        }
+        if !Self::REFRESH_INTERVALS.contains(&self.refresh_interval) {
+            return Err(anyhow!(tr!(
+                "error-refresh-interval",
+                min = *Self::REFRESH_INTERVALS.start(),
+                max = *Self::REFRESH_INTERVALS.end(),
+            )));
+        }
+        Ok(())
+    }
+
     pub fn read<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let contents = fs::read_to_string(&path).map_err(|err| {
-            anyhow!("Ошибка чтения конфига ({err}). Проверьте его наличие и доступ к нему")
-        })?;
-        let data = toml::from_str(&contents).map_err(|err| {
-            anyhow!("Ошибка парсинга конфига. Проверьте его корректность.\n\n{err}")
-        })?;
+        let contents = fs::read_to_string(&path)
+            .map_err(|err| anyhow!(tr!("error-config-read", error = err.to_string())))?;
+        let mut data: Self = toml::from_str(&contents)
+            .map_err(|err| anyhow!(tr!("error-config-parse", error = err.to_string())))?;
+        data.migrate();
         Ok(data)
     }
 
+    /// Moves the settings of the previous versions to the current ones.
+    /// The old keys are dropped from the file on the next saving
+    fn migrate(&mut self) {
+        // The only IP geolocation service used to be set by
+        // `[api] ip_location`, so it's kept the first one
+        if let Some(url) = self.api.ip_location.take() {
+            let provider = geoip::Provider::IpApi(geoip::IpApi { url });
+            if !self.geoip.contains(&provider) {
+                self.geoip.insert(0, provider);
+            }
+        }
+    }
+
+    /// Writes the config atomically: the new file replaces the old one
+    /// only after it's completely written. The previous version is kept in
+    /// `<path>.bak`
     pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<()> {
-        let data = toml::to_string(&self).map_err(|err| {
-            anyhow!("Ошибка сериализации конфига. Проверьте корректность данных.\n\n{err}")
-        })?;
-        fs::write(path, data).map_err(|err| {
-            anyhow!("Ошибка записи конфига ({err}). Проверьте доступ к нему в ФС.")
+        let path = path.as_ref();
+        let data = toml::to_string(&self)
+            .map_err(|err| anyhow!(tr!("error-config-serialize", error = err.to_string())))?;
+
+        let sibling = |ext: &str| {
+            let mut name = OsString::from(path.as_os_str());
+            name.push(ext);
+            PathBuf::from(name)
+        };
+        let tmp = sibling(".tmp");
+
+        let write = || -> std::io::Result<()> {
+            let mut file = fs::File::create(&tmp)?;
+            file.write_all(data.as_bytes())?;
+            file.sync_all()?;
+
+            if path.exists() {
+                fs::copy(path, sibling(".bak"))?;
+            }
+            fs::rename(&tmp, path)?;
+
+            // The rename isn't durable until the directory entry is
+            // written: a power loss may bring the old file back
+            let dir = match path.parent() {
+                Some(dir) if !dir.as_os_str().is_empty() => dir,
+                _ => Path::new("."),
+            };
+            fs::File::open(dir)?.sync_all()
+        };
+        write().map_err(|err| {
+            let _ = fs::remove_file(&tmp);
+            anyhow!(tr!("error-config-write", error = err.to_string()))
         })?;
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn api_urls_test() {
+        let conf: Config = toml::from_str(
+            r#"
+            units = "metric"
+            appid = "key"
+            autodetect_location = false
+
+            [api]
+            current = "http://127.0.0.1:8080/weather"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(conf.api.current, "http://127.0.0.1:8080/weather");
+        assert_eq!(conf.api.daily, daily::API_URL);
+        assert_eq!(conf.geoip, geoip::Provider::defaults());
+        assert_eq!(conf.refresh_interval, 30);
+        assert_eq!(conf.interface, Interface::default());
+        // Configs older than the translations stay Russian
+        assert_eq!(conf.interface.language, Language::Ru);
+    }
+
+    #[test]
+    fn migrate_test() {
+        let dir = std::env::temp_dir().join("ice-test-migrate");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("ice.toml");
+        fs::write(
+            &path,
+            r#"
+            units = "metric"
+            appid = "key"
+            autodetect_location = true
+
+            [api]
+            ip_location = "http://127.0.0.1:8080/json/"
+            "#,
+        )
+        .unwrap();
+
+        let conf = Config::read(&path).unwrap();
+        assert_eq!(conf.api.ip_location, None);
+        assert_eq!(
+            conf.geoip[0],
+            geoip::Provider::IpApi(geoip::IpApi {
+                url: "http://127.0.0.1:8080/json/".to_string()
+            })
+        );
+        assert_eq!(conf.geoip[1..], geoip::Provider::defaults());
+
+        conf.write(&path).unwrap();
+        assert!(!fs::read_to_string(&path).unwrap().contains("ip_location"));
+    }
+
+    #[test]
+    fn validate_test() {
+        let mut conf = Config::default();
+        assert!(conf.validate().is_ok());
+
+        conf.appid = "not a key".to_string();
+        assert!(conf.validate().is_err());
+
+        conf = Config {
+            autodetect_location: false,
+            ..Config::default()
+        };
+        assert!(conf.validate().is_err());
+        conf.coords = Some((56.3, 43.9));
+        assert!(conf.validate().is_ok());
+
+        conf.refresh_interval = 0;
+        assert!(conf.validate().is_err());
+    }
+
+    #[test]
+    fn write_test() {
+        let dir = std::env::temp_dir().join("ice-test-config");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("ice.toml");
+
+        let mut conf = Config::default();
+        conf.write(&path).unwrap();
+        assert!(!dir.join("ice.toml.bak").exists());
+
+        conf.units = Units::Imperial;
+        conf.write(&path).unwrap();
+        assert_eq!(Config::read(&path).unwrap().units, Units::Imperial);
+        assert_eq!(
+            Config::read(dir.join("ice.toml.bak")).unwrap().units,
+            Units::Metric
+        );
+        assert!(!dir.join("ice.toml.tmp").exists());
+
+        assert!(conf.write(dir.join("missing/ice.toml")).is_err());
+        assert!(!dir.join("missing/ice.toml.tmp").exists());
+    }
+}
@@ -10,4 +10,14 @@ pub const GEO_CACHE: &str = "geo.json";
 pub const CURRENT_CACHE: &str = "current.json";
 pub const DAILY_CACHE: &str = "daily.json";
 
+/// GeoNames city database for the offline geocoding. It is shipped in the
+/// package (prepared by `assets/cities.sh`) and is optional otherwise
+pub const CITIES_DB_PATH: &str = "/usr/share/ice/cities15000.txt";
+
 pub const DEFAULT_WIN_SIZE: (u16, u16) = (800, 480);
+
+/// Short hash of the git commit the program is built from
+pub const GIT_COMMIT: &str = env!("ICE_GIT_COMMIT");
+
+/// Build date (UTC, `YYYY-MM-DD`)
+pub const BUILD_DATE: &str = env!("ICE_BUILD_DATE");
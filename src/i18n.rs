@@ -0,0 +1,229 @@
+//! Localization of the interface
+//!
+//! Strings are stored in the Fluent files (`locales/*.ftl`) which are
+//! embedded into the program. The language is global, so the texts made
+//! outside of the UI (e.g. error messages) are translated too, and may be
+//! switched at runtime with [`set_language`].
+//!
+//! ```ignore
+//! use crate::i18n::tr;
+//!
+//! let title = tr!("settings-title");
+//! let updated = tr!("time-minutes-ago", minutes = 5);
+//! ```
+
+use std::{
+    env,
+    sync::{
+        OnceLock,
+        atomic::{AtomicU8, Ordering},
+    },
+};
+
+use fluent::{FluentArgs, FluentResource, concurrent::FluentBundle};
+use unic_langid::{LanguageIdentifier, langid};
+
+use crate::config::Language;
+
+type Bundle = FluentBundle<FluentResource>;
+
+/// Translations in order of the [`LANGUAGES`]
+const SOURCES: [&str; 2] = [
+    include_str!("../locales/ru.ftl"),
+    include_str!("../locales/en.ftl"),
+];
+
+/// Languages which have the translations
+const LANGUAGES: [Language; 2] = [Language::Ru, Language::En];
+
+/// Index of the current language in the [`LANGUAGES`]. `u8::MAX` if the
+/// language isn't set yet
+static CURRENT: AtomicU8 = AtomicU8::new(u8::MAX);
+
+static BUNDLES: OnceLock<Vec<Bundle>> = OnceLock::new();
+
+impl Language {
+    fn id(self) -> LanguageIdentifier {
+        match self {
+            Self::Ru => langid!("ru"),
+            Self::En | Self::Auto => langid!("en"),
+        }
+    }
+
+    /// Code of the language (`ru`, `en`), e.g. for the `lang` attribute of
+    /// the HTML pages
+    pub fn code(self) -> &'static str {
+        match self {
+            Self::Ru => "ru",
+            Self::En | Self::Auto => "en",
+        }
+    }
+
+    /// Detects the language from the locale (`LC_ALL`, `LC_MESSAGES` and
+    /// `LANG` variables). English is used for the languages without the
+    /// translation
+    pub fn from_env() -> Self {
+        let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .into_iter()
+            .filter_map(|var| env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .unwrap_or_default();
+        Self::from_locale(&locale)
+    }
+
+    /// Parses the locale name like `ru_KZ.UTF-8`
+    fn from_locale(locale: &str) -> Self {
+        let lang = locale
+            .split(['_', '.', '@', '-'])
+            .next()
+            .unwrap_or_default();
+        if lang.eq_ignore_ascii_case("ru") {
+            Self::Ru
+        } else {
+            Self::En
+        }
+    }
+
+    /// Returns the language to use ([`Language::Auto`] is detected from the
+    /// environment)
+    pub fn resolve(self) -> Self {
+        match self {
+            Self::Auto => Self::from_env(),
+            lang => lang,
+        }
+    }
+}
+
+fn bundles() -> &'static [Bundle] {
+    BUNDLES.get_or_init(|| {
+        LANGUAGES
+            .iter()
+            .zip(SOURCES)
+            .map(|(lang, source)| {
+                let resource = FluentResource::try_new(source.to_string())
+                    .unwrap_or_else(|(resource, _)| resource);
+                let mut bundle = Bundle::new_concurrent(vec![lang.id()]);
+                // Unicode isolation marks are shown as boxes by the fonts
+                bundle.set_use_isolating(false);
+                bundle
+                    .add_resource(resource)
+                    .expect("duplicate messages in the translation");
+                bundle
+            })
+            .collect()
+    })
+}
+
+fn index(lang: Language) -> u8 {
+    let lang = lang.resolve();
+    LANGUAGES
+        .iter()
+        .position(|l| *l == lang)
+        .unwrap_or_default() as u8
+}
+
+/// Switches the language of the interface
+pub fn set_language(lang: Language) {
+    CURRENT.store(index(lang), Ordering::Relaxed);
+}
+
+/// Returns the current language (never [`Language::Auto`])
+pub fn language() -> Language {
+    match CURRENT.load(Ordering::Relaxed) {
+        u8::MAX => {
+            let lang = Language::Auto.resolve();
+            CURRENT.store(index(lang), Ordering::Relaxed);
+            lang
+        }
+        idx => LANGUAGES[idx as usize],
+    }
+}
+
+/// Formats the message `id` in the `lang`. Falls back to the Russian (the
+/// most complete translation) and then to the `id` itself
+pub fn translate_in(lang: Language, id: &str, args: Option<&FluentArgs>) -> String {
+    let bundles = bundles();
+    [index(lang), 0]
+        .into_iter()
+        .map(|idx| &bundles[idx as usize])
+        .find_map(|bundle| {
+            let pattern = bundle.get_message(id)?.value()?;
+            let mut errors = vec![];
+            Some(
+                bundle
+                    .format_pattern(pattern, args, &mut errors)
+                    .into_owned(),
+            )
+        })
+        .unwrap_or_else(|| id.to_string())
+}
+
+/// Formats the message `id` in the current language
+pub fn translate(id: &str, args: Option<&FluentArgs>) -> String {
+    translate_in(language(), id, args)
+}
+
+/// Returns the translation of the message with the optional named
+/// arguments: `tr!("id")`, `tr!("id", name = value)`
+macro_rules! tr {
+    ($id:expr) => {
+        $crate::i18n::translate($id, None)
+    };
+    ($id:expr, $($name:ident = $value:expr),+ $(,)?) => {{
+        let mut args = fluent::FluentArgs::new();
+        $(args.set(stringify!($name), $value);)+
+        $crate::i18n::translate($id, Some(&args))
+    }};
+}
+
+pub(crate) use tr;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Identifiers of the messages of the translation
+    fn ids(source: &str) -> Vec<&str> {
+        assert!(FluentResource::try_new(source.to_string()).is_ok());
+        source
+            .lines()
+            .filter(|line| line.starts_with(|c: char| c.is_ascii_alphabetic()))
+            .filter_map(|line| line.split_once('=').map(|(id, _)| id.trim()))
+            .collect()
+    }
+
+    #[test]
+    fn translations_test() {
+        let ru = ids(SOURCES[0]);
+        let en = ids(SOURCES[1]);
+        let missing = ru.iter().filter(|id| !en.contains(id)).collect::<Vec<_>>();
+        let extra = en.iter().filter(|id| !ru.contains(id)).collect::<Vec<_>>();
+        assert!(missing.is_empty(), "not translated to English: {missing:?}");
+        assert!(extra.is_empty(), "missing in Russian: {extra:?}");
+    }
+
+    #[test]
+    fn translate_test() {
+        let mut args = FluentArgs::new();
+        args.set("minutes", 5);
+        assert_eq!(
+            translate_in(Language::En, "time-minutes-ago", Some(&args)),
+            "5 min ago"
+        );
+        assert_eq!(
+            translate_in(Language::Ru, "time-minutes-ago", Some(&args)),
+            "5 мин назад"
+        );
+        assert_eq!(translate_in(Language::En, "no-such-id", None), "no-such-id");
+    }
+
+    #[test]
+    fn locale_test() {
+        assert_eq!(Language::from_locale("ru_KZ.UTF-8"), Language::Ru);
+        assert_eq!(Language::from_locale("ru"), Language::Ru);
+        assert_eq!(Language::from_locale("de_DE.UTF-8"), Language::En);
+        assert_eq!(Language::from_locale("sr_RS@latin"), Language::En);
+        assert_eq!(Language::from_locale("C"), Language::En);
+        assert_eq!(Language::from_locale(""), Language::En);
+    }
+}
@@ -2,9 +2,10 @@ pub mod api;
 pub mod app;
 pub mod config;
 pub mod consts;
+pub mod i18n;
+pub mod time;
 pub mod ui;
 pub mod units;
-pub mod time;
 
 fn main() -> iced::Result {
     ui::ui()
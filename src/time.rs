@@ -1,11 +1,28 @@
 //! Time&date formatting
 
 use chrono::{DateTime, Datelike, TimeZone, Timelike};
+use serde::{Deserialize, Serialize};
 use std::fmt::{Debug, Display};
 
+use crate::i18n::tr;
+
 pub struct Time<'a, D: TimeZone + Debug> {
     time: &'a DateTime<D>,
     display_mode: DisplayMode,
+    format: TimeFormat,
+}
+
+/// Clock format
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeFormat {
+    /// 0-23 hours
+    #[default]
+    #[serde(rename = "24h")]
+    H24,
+
+    /// 1-12 hours with AM/PM
+    #[serde(rename = "12h")]
+    H12,
 }
 
 #[derive(Clone, Copy, Default)]
@@ -29,6 +46,7 @@ impl<'a, D: TimeZone + Debug> Time<'a, D> {
         Self {
             time,
             display_mode: DisplayMode::default(),
+            format: TimeFormat::default(),
         }
     }
 
@@ -36,12 +54,24 @@ impl<'a, D: TimeZone + Debug> Time<'a, D> {
         self.display_mode = mode;
         self
     }
+
+    pub fn set_format(mut self, format: TimeFormat) -> Self {
+        self.format = format;
+        self
+    }
 }
 
 impl<'a, D: TimeZone + Debug> Display for Time<'a, D> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let time = &self.time;
-        let h_zero = if time.hour() < 10 { "0" } else { "" };
+        let (hour, suffix) = match self.format {
+            TimeFormat::H24 => (time.hour(), ""),
+            TimeFormat::H12 => {
+                let (pm, hour) = time.hour12();
+                (hour, if pm { " PM" } else { " AM" })
+            }
+        };
+        let h_zero = if hour < 10 { "0" } else { "" };
         let m_zero = if time.minute() < 10 { "0" } else { "" };
         let s_zero = if time.second() < 10 { "0" } else { "" };
 
@@ -50,13 +80,13 @@ impl<'a, D: TimeZone + Debug> Display for Time<'a, D> {
 
         write!(
             f,
-            "{}",
+            "{}{suffix}",
             match self.display_mode {
                 DisplayMode::TimeDefault =>
-                    format!("{}{}:{}{}", h_zero, time.hour(), m_zero, time.minute()),
+                    format!("{}{}:{}{}", h_zero, hour, m_zero, time.minute()),
                 DisplayMode::TimeWithSeconds => format!(
                     "{}:{}{}:{}{}",
-                    time.hour(),
+                    hour,
                     m_zero,
                     time.minute(),
                     s_zero,
@@ -69,7 +99,7 @@ impl<'a, D: TimeZone + Debug> Display for Time<'a, D> {
                     month_zero,
                     time.month(),
                     time.year(),
-                    time.hour(),
+                    hour,
                     m_zero,
                     time.minute()
                 ),
@@ -80,7 +110,7 @@ impl<'a, D: TimeZone + Debug> Display for Time<'a, D> {
                     month_zero,
                     time.month(),
                     time.year(),
-                    time.hour(),
+                    hour,
                     m_zero,
                     time.minute(),
                     s_zero,
@@ -90,3 +120,24 @@ impl<'a, D: TimeZone + Debug> Display for Time<'a, D> {
         )
     }
 }
+
+/// Formats the time elapsed since some event (`5 min ago`)
+pub fn ago(seconds: i64) -> String {
+    match seconds {
+        ..60 => tr!("time-just-now"),
+        60..3600 => tr!("time-minutes-ago", minutes = seconds / 60),
+        3600..86400 => tr!("time-hours-ago", hours = seconds / 3600),
+        _ => tr!("time-days-ago", days = seconds / 86400),
+    }
+}
+
+/// Formats the long duration (`3 d 04:05`)
+pub fn duration(seconds: u64) -> String {
+    let (days, hours, minutes) = (seconds / 86400, seconds / 3600 % 24, seconds / 60 % 60);
+    let time = format!("{hours:02}:{minutes:02}");
+    if days > 0 {
+        tr!("time-days-duration", days = days, time = time)
+    } else {
+        time
+    }
+}
@@ -1,6 +1,7 @@
 //! User interface based on `iced`
 
 pub mod modal;
+pub mod notification;
 pub mod styles;
 pub mod widgets;
 
@@ -9,6 +10,7 @@ pub mod daily;
 
 pub mod network;
 pub mod settings;
+pub mod system;
 pub mod weather;
 
 pub mod update;
@@ -16,19 +18,26 @@ pub mod view;
 
 use std::{fmt::Debug, time::Duration};
 
-use chrono::{DateTime, Timelike};
+use chrono::{DateTime, NaiveDate, Timelike};
 use iced::{
     Alignment::Center,
-    Element, Subscription, Task, Theme, time,
-    widget::{button, center, column, container, horizontal_space, image, row, scrollable, text},
+    Element, Length, Subscription, Task, Theme, time,
+    widget::{button, column, container, horizontal_space, image, row, stack, text},
 };
 
+use self::notification::{Action, Notification, Severity, Topic};
 use crate::{
-    api::{current::Current, daily::Daily, floor, geocoding},
-    app::location,
-    config::Config,
+    api::{current::Current, daily::Daily, geocoding},
+    app::{
+        cities::CityDb,
+        location,
+        network::{Connectivity, NetworkChange},
+        power, provision,
+        system::{Info, Snapshot, System},
+    },
+    config::{Config, ThemeMode},
     consts::CONF_PATH,
-    units::Variant,
+    i18n::{self, tr},
 };
 
 pub fn ui() -> iced::Result {
@@ -41,16 +50,67 @@ pub fn ui() -> iced::Result {
         .run()
 }
 
+/// Page shown between the top panel and the bottom bar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Page {
+    #[default]
+    Weather,
+    /// Details of the current weather
+    Current,
+    Daily,
+    Network,
+    System,
+    Settings,
+}
+
 pub struct Ice {
     conf: Config,
-    error_text: Option<String>,
+    notifications: notification::Notifications,
+    page: Page,
 
     current_weather: Option<Current>,
+    /// Time of the last successful weather update
+    weather_updated: Option<chrono::DateTime<chrono::Local>>,
+    /// Time of the last weather request
+    weather_requested: Option<chrono::DateTime<chrono::Local>>,
+    weather_pending: bool,
     daily_weather: Option<Daily>,
+    /// Time of the last forecast request
+    daily_requested: Option<chrono::DateTime<chrono::Local>>,
+    daily_pending: bool,
+    /// Bumped when the location or the settings change, so the responses
+    /// to the earlier requests are dropped
+    weather_generation: u64,
+    /// Day which 3-hour forecast is shown on the daily page
+    expanded_day: Option<NaiveDate>,
     geocoding: Option<geocoding::Location>,
-    autodetected_location: Option<location::Location>,
+    location: Option<location::Resolved>,
+    location_pending: bool,
+    /// Time of the last location detection
+    location_requested: Option<chrono::DateTime<chrono::Local>>,
+    /// Failed detections in a row, the retry delay grows with them
+    location_failures: u32,
+    /// Detection ignoring the cache is requested while another one is
+    /// running, it's started when the current one finishes
+    location_redetect: bool,
+    network_state: Option<u32>,
+    connectivity: Option<Connectivity>,
+    wifi_signal: Option<u8>,
+    primary_connection: Option<String>,
     selected_location: Option<geocoding::LocationInfo>,
 
+    modal: Option<modal::Modal>,
+    location_query: String,
+
+    network: network::NetworkPage,
+    provisioning: Option<provision::Status>,
+    system: system::SystemPage,
+    settings: settings::SettingsPage,
+    power_permissions: Vec<(power::Action, power::Permission)>,
+    /// Error of the last power menu action
+    power_status: Option<String>,
+    about: Option<Info>,
+
     uptime: u32,
     ctime: chrono::DateTime<chrono::Local>,
 }
@@ -60,17 +120,60 @@ pub enum Message {
     /*****************
      * Async actions *
      *****************/
-    AutoDetectLocation,
-    LocationReceived((Option<location::Location>, Option<String>)),
+    ResolveLocation,
+    /// Detect the location again ignoring the cache
+    RedetectLocation,
+    LocationResolved((Option<location::Resolved>, Option<String>)),
+    LocationNameReceived(Option<String>),
 
     GetCurrentWeather,
-    CurrentWeatherReceived((Option<Current>, Option<String>)),
+    CurrentWeatherReceived(u64, (Option<Current>, Option<String>)),
+    GetDailyWeather,
+    DailyWeatherReceived(u64, (Option<Daily>, Option<String>)),
+
+    SearchLocation,
+    GeocodingReceived((Option<geocoding::Location>, Option<String>)),
+
+    /*********
+     * Pages *
+     *********/
+    ShowPage(Page),
+    Network(network::NetworkMessage),
+    Settings(settings::SettingsMessage),
+    /// Expand or collapse the day on the daily forecast page
+    ToggleDay(NaiveDate),
+
+    /**********
+     * Modals *
+     **********/
+    ShowModal(modal::Modal),
+    HideModal,
+    LocationQueryChanged(String),
+    LocationSelected(geocoding::LocationInfo),
+    PowerPermissions(Vec<(power::Action, power::Permission)>),
+    PowerConfirmed(power::Action),
+    /// Power menu action is finished (with the optional error)
+    PowerDone(power::Action, Option<String>),
+    AboutReceived(Info),
 
     /************************
      * Some service actions *
      ************************/
     UpdateCTime,
     UpdateUptime,
+    NetworkChanged(NetworkChange),
+    Provisioning(provision::Status),
+    /// Provisioning via the hotspot is finished (with the optional error
+    /// and the optional warning)
+    Provisioned(Option<String>, Option<String>),
+    SampleSystem,
+    SystemSampled((Option<Snapshot>, Option<String>)),
+
+    /*****************
+     * Notifications *
+     *****************/
+    DismissNotification(notification::Topic),
+    NotificationAction(notification::Topic, notification::Action),
 
     /*****************
      * Button clicks *
@@ -80,14 +183,49 @@ pub enum Message {
 
 impl Default for Ice {
     fn default() -> Self {
+        let mut notifications = notification::Notifications::default();
+        let conf = Config::read(CONF_PATH).unwrap_or_else(|why| {
+            notifications.push(
+                Notification::new(Topic::Config, Severity::Error, why.to_string())
+                    .action(Action::Settings),
+            );
+            Config::default()
+        });
+        i18n::set_language(conf.interface.language);
+
         Self {
-            conf: Config::read(CONF_PATH).unwrap_or_default(),
-            error_text: None,
+            conf,
+            notifications,
+            page: Page::default(),
             current_weather: None,
+            weather_updated: None,
+            weather_requested: None,
+            weather_pending: false,
             daily_weather: None,
+            daily_requested: None,
+            daily_pending: false,
+            weather_generation: 0,
+            expanded_day: None,
             geocoding: None,
-            autodetected_location: None,
+            location: None,
+            location_pending: false,
+            location_requested: None,
+            location_failures: 0,
+            location_redetect: false,
+            network_state: None,
+            connectivity: None,
+            wifi_signal: None,
+            primary_connection: None,
             selected_location: None,
+            modal: None,
+            location_query: String::new(),
+            network: network::NetworkPage::default(),
+            provisioning: None,
+            system: system::SystemPage::default(),
+            settings: settings::SettingsPage::default(),
+            power_permissions: Vec::new(),
+            power_status: None,
+            about: None,
             uptime: 0,
             ctime: chrono::offset::Local::now(),
         }
@@ -98,12 +236,35 @@ impl Ice {
     const TOP_PANEL_TEXT_SIZE: u16 = 25;
     const TEXT_SIZE: u16 = 20;
 
+    /// Failed weather request is repeated after this time (in seconds).
+    /// The shorter one is used while there is no weather at all
+    const WEATHER_RETRY: i64 = 60;
+    const WEATHER_RETRY_NO_DATA: i64 = 5;
+
+    /// Weather request without the response is forgotten after this time
+    /// (in seconds). HTTP requests have their own timeouts, this one
+    /// guarantees that the updates never stop
+    const WEATHER_TIMEOUT: i64 = 2 * crate::api::REQUEST_TIMEOUT.as_secs() as i64;
+
+    /// Failed location detection is repeated after this time (in seconds),
+    /// doubled after each failure up to the max one
+    const LOCATION_RETRY: i64 = 5;
+    const LOCATION_RETRY_MAX: i64 = 300;
+
+    /// The detected location is checked again after this time (in
+    /// seconds): the public IP may change without any network event
+    const LOCATION_REFRESH: i64 = 60 * 60;
+
+    /// Clock jump (in seconds) which means that the device has woken up
+    /// from the suspend
+    const WAKE_GAP: i64 = 60;
+
     pub fn theme(&self) -> Theme {
-        let h = self.ctime.hour();
-        if h >= 6 && h < 22 {
-            Theme::GruvboxLight
-        } else {
-            Theme::GruvboxDark
+        match self.conf.interface.theme {
+            ThemeMode::Light => Theme::GruvboxLight,
+            ThemeMode::Dark => Theme::GruvboxDark,
+            ThemeMode::Auto if (6..22).contains(&self.ctime.hour()) => Theme::GruvboxLight,
+            ThemeMode::Auto => Theme::GruvboxDark,
         }
     }
 
@@ -111,15 +272,13 @@ impl Ice {
         let mut scripts = vec![
             time::every(Duration::from_millis(500)).map(|_| Message::UpdateCTime),
             time::every(Duration::from_secs(1)).map(|_| Message::UpdateUptime),
+            Subscription::run(self::network::watch_network),
+            Subscription::run(self::network::provision),
+            time::every(system::SAMPLE_INTERVAL).map(|_| Message::SampleSystem),
         ];
 
-        if self.autodetected_location.is_none() {
-            scripts
-                .push(time::every(Duration::from_millis(500)).map(|_| Message::AutoDetectLocation));
-        }
-
-        if self.current_weather.is_none() {
-            scripts.push(time::every(Duration::from_secs(1)).map(|_| Message::GetCurrentWeather));
+        if self.location.is_none() {
+            scripts.push(time::every(Duration::from_secs(1)).map(|_| Message::ResolveLocation));
         }
 
         Subscription::batch(scripts)
@@ -130,52 +289,319 @@ impl Ice {
             /*****************
              * Async actions *
              *****************/
-            Message::AutoDetectLocation => Task::perform(
-                async move {
-                    let loc = location::Location::get_by_ip().await;
-                    match loc {
-                        Ok(loc) => (Some(loc), None),
-                        Err(why) => (None, Some(why.to_string())),
+            Message::ResolveLocation if !self.location_due() => Task::none(),
+            Message::ResolveLocation => self.resolve_location(false),
+            Message::RedetectLocation => self.resolve_location(true),
+            Message::LocationResolved((loc, error)) => {
+                self.location_pending = false;
+                self.location_failures = match loc {
+                    Some(_) => 0,
+                    None => self.location_failures.saturating_add(1),
+                };
+                // The network has changed during the detection
+                let redetect = if std::mem::take(&mut self.location_redetect) {
+                    Task::done(Message::RedetectLocation)
+                } else {
+                    Task::none()
+                };
+                self.report(Topic::Location, error, |error| {
+                    Notification::new(Topic::Location, Severity::Warning, error)
+                        .action(Action::Retry(Box::new(Message::RedetectLocation)))
+                        .action(Action::Settings)
+                });
+
+                // The responses for the previous location are dropped
+                let moved = loc.as_ref().is_some_and(|loc| {
+                    self.location
+                        .as_ref()
+                        .is_none_or(|old| (old.lat, old.lon) != (loc.lat, loc.lon))
+                });
+                if moved {
+                    self.invalidate_weather();
+                }
+
+                // The location is detected again after the network change
+                // or the wake up, so the weather is outdated too
+                let weather = if self.current_weather.is_some() || moved {
+                    Task::done(Message::GetCurrentWeather)
+                } else {
+                    Task::none()
+                };
+
+                // The previous location is kept if the detection has failed
+                let Some(loc) = loc else {
+                    return Task::batch([weather, redetect]);
+                };
+                self.location = Some(loc);
+
+                let name = match &self.location {
+                    Some(loc) if loc.name.is_none() => {
+                        let url = self.conf.api.reverse_geocoding.clone();
+                        let appid = self.conf.appid.clone();
+                        let loc = crate::api::Location::from(loc);
+
+                        Task::perform(
+                            async move {
+                                let online = geocoding::Location::get_reverse(&url, loc, &appid)
+                                    .await
+                                    .ok()
+                                    .and_then(|geo| geo.0.into_iter().next());
+                                let info = match online {
+                                    Some(info) => info,
+                                    None => CityDb::bundled().await?.nearest(loc.lat, loc.lon)?,
+                                };
+                                Some(info.to_string())
+                            },
+                            Message::LocationNameReceived,
+                        )
                     }
-                },
-                |val| Message::LocationReceived(val),
-            ),
-            Message::LocationReceived(loc) => {
-                (self.autodetected_location, self.error_text) = loc;
+                    _ => Task::none(),
+                };
+
+                Task::batch([weather, name, redetect])
+            }
+            Message::LocationNameReceived(name) => {
+                if let Some(loc) = &mut self.location {
+                    loc.name = name;
+                }
                 Task::none()
             }
 
             Message::GetCurrentWeather => {
+                if self.weather_pending {
+                    return Task::none();
+                }
+                self.weather_pending = true;
+                self.weather_requested = Some(chrono::offset::Local::now());
+
+                let url = self.conf.api.current.clone();
                 let appid = self.conf.appid.clone();
                 let units = self.conf.units;
-                let location = self.autodetected_location.clone();
+                let location = self.location.as_ref().map(crate::api::Location::from);
+                let generation = self.weather_generation;
 
                 Task::perform(
                     async move {
                         if let Some(loc) = location {
-                            let current = Current::get(
-                                appid,
-                                crate::api::Location {
-                                    lat: loc.lat,
-                                    lon: loc.lon,
-                                },
-                                units,
-                            )
-                            .await;
+                            let current = Current::get(&url, appid, loc, units).await;
 
                             match current {
                                 Ok(current) => (Some(current), None),
                                 Err(why) => (None, Some(why.to_string())),
                             }
                         } else {
-                            (None, Some("Неизвестное местоположение".to_string()))
+                            (None, Some(tr!("error-unknown-location")))
+                        }
+                    },
+                    move |current| Message::CurrentWeatherReceived(generation, current),
+                )
+            }
+            Message::CurrentWeatherReceived(generation, current) => {
+                // The location or the settings have been changed while the
+                // request was running
+                if generation != self.weather_generation {
+                    return Task::none();
+                }
+                self.weather_pending = false;
+                let (current, error) = current;
+
+                self.report(Topic::Weather, error, |error| {
+                    Notification::new(Topic::Weather, Severity::Warning, error)
+                        .action(Action::Retry(Box::new(Message::GetCurrentWeather)))
+                        .action(Action::Network)
+                });
+                // The previous weather is shown until the next success
+                if current.is_some() {
+                    self.current_weather = current;
+                    self.weather_updated = Some(chrono::offset::Local::now());
+                    // The forecast is updated together with the current weather
+                    return Task::done(Message::GetDailyWeather);
+                }
+                Task::none()
+            }
+            Message::GetDailyWeather => {
+                let Some(loc) = self.location.as_ref().map(crate::api::Location::from) else {
+                    return Task::none();
+                };
+                if self.daily_pending {
+                    return Task::none();
+                }
+                self.daily_pending = true;
+                self.daily_requested = Some(chrono::offset::Local::now());
+
+                let url = self.conf.api.daily.clone();
+                let appid = self.conf.appid.clone();
+                let units = self.conf.units;
+                let generation = self.weather_generation;
+
+                Task::perform(
+                    async move {
+                        match Daily::get(&url, &appid, loc, units).await {
+                            Ok(daily) => (Some(daily), None),
+                            Err(why) => (None, Some(why.to_string())),
+                        }
+                    },
+                    move |daily| Message::DailyWeatherReceived(generation, daily),
+                )
+            }
+            Message::DailyWeatherReceived(generation, daily) => {
+                if generation != self.weather_generation {
+                    return Task::none();
+                }
+                self.daily_pending = false;
+                let (daily, error) = daily;
+
+                self.report(Topic::Forecast, error, |error| {
+                    Notification::new(Topic::Forecast, Severity::Warning, error)
+                        .action(Action::Retry(Box::new(Message::GetDailyWeather)))
+                });
+                if daily.is_some() {
+                    self.daily_weather = daily;
+                }
+                Task::none()
+            }
+
+            Message::SearchLocation => {
+                let urls = self.conf.api.clone();
+                let appid = self.conf.appid.clone();
+                let query = self.location_query.trim().to_string();
+
+                Task::perform(
+                    async move {
+                        // Postal code is entered as `606000` or `606000,RU`
+                        let online = if query.starts_with(|c: char| c.is_ascii_digit()) {
+                            let (zip, country) = match query.split_once(',') {
+                                Some((zip, country)) => (zip.trim(), Some(country.trim())),
+                                None => (query.as_str(), None),
+                            };
+                            geocoding::ZipInfo::get(&urls.zip_geocoding, zip, country, &appid)
+                                .await
+                                .map(|zip| geocoding::Location(vec![zip.into()]))
+                        } else {
+                            geocoding::Location::get(&urls.geocoding, &query, &appid).await
+                        };
+                        match online {
+                            Ok(geo) => (Some(geo), None),
+                            Err(why) => match CityDb::bundled().await {
+                                // The network or the geocoding API is unavailable
+                                Some(db) => (
+                                    Some(geocoding::Location(
+                                        db.search(&query, geocoding::LIMIT.into()),
+                                    )),
+                                    Some(why.to_string()),
+                                ),
+                                None => (None, Some(why.to_string())),
+                            },
                         }
                     },
-                    |val| Message::CurrentWeatherReceived(val),
+                    Message::GeocodingReceived,
                 )
             }
-            Message::CurrentWeatherReceived(current) => {
-                (self.current_weather, self.error_text) = current;
+            Message::GeocodingReceived(geo) => {
+                let error;
+                (self.geocoding, error) = geo;
+                self.report(Topic::Search, error, |error| {
+                    Notification::new(Topic::Search, Severity::Warning, error)
+                        .action(Action::Retry(Box::new(Message::SearchLocation)))
+                });
+                Task::none()
+            }
+
+            /*********
+             * Pages *
+             *********/
+            Message::ShowPage(page) => {
+                self.page = page;
+                match page {
+                    Page::Weather | Page::Current => Task::none(),
+                    Page::Daily if self.daily_weather.is_none() => {
+                        Task::done(Message::GetDailyWeather)
+                    }
+                    Page::Daily => Task::none(),
+                    Page::Network => network::refresh(),
+                    Page::System => Task::done(Message::SampleSystem),
+                    Page::Settings => {
+                        self.settings.open(&self.conf);
+                        Task::none()
+                    }
+                }
+            }
+            Message::Network(message) => self.update_network(message),
+            Message::Settings(message) => self.update_settings(message),
+            Message::ToggleDay(date) => {
+                self.expanded_day = (self.expanded_day != Some(date)).then_some(date);
+                Task::none()
+            }
+
+            /**********
+             * Modals *
+             **********/
+            Message::ShowModal(modal) => {
+                self.modal = Some(modal);
+                match modal {
+                    modal::Modal::Power => {
+                        self.power_status = None;
+                        Task::perform(power::permissions(), |permissions| {
+                            Message::PowerPermissions(permissions.unwrap_or_default())
+                        })
+                    }
+                    // IP addresses may change, so the information is read
+                    // every time
+                    modal::Modal::About => Task::perform(
+                        async {
+                            tokio::task::spawn_blocking(|| System::default().info())
+                                .await
+                                .unwrap_or_default()
+                        },
+                        Message::AboutReceived,
+                    ),
+                    _ => Task::none(),
+                }
+            }
+            Message::HideModal => {
+                self.modal = None;
+                Task::none()
+            }
+            Message::LocationQueryChanged(query) => {
+                self.location_query = query;
+                Task::none()
+            }
+            Message::AboutReceived(info) => {
+                self.about = Some(info);
+                Task::none()
+            }
+            Message::PowerPermissions(permissions) => {
+                self.power_permissions = permissions;
+                Task::none()
+            }
+            Message::PowerConfirmed(action) => {
+                self.modal = Some(modal::Modal::Power);
+                Task::perform(power::run(action), move |result| {
+                    Message::PowerDone(action, result.err().map(|why| why.to_string()))
+                })
+            }
+            Message::PowerDone(action, error) => match error {
+                Some(error) => {
+                    self.power_status = Some(error);
+                    Task::none()
+                }
+                None if action == power::Action::ExitToConsole => iced::exit(),
+                // The system is going down
+                None => {
+                    self.modal = None;
+                    Task::none()
+                }
+            },
+            Message::LocationSelected(loc) => {
+                // The city is a setting like the others: it's applied by the
+                // "Save" button and may be discarded by "Reset"
+                if self.page != Page::Settings {
+                    self.settings.open(&self.conf);
+                    self.page = Page::Settings;
+                }
+                self.settings.set_coords((loc.lat, loc.lon));
+                self.selected_location = Some(loc);
+                self.modal = None;
                 Task::none()
             }
 
@@ -183,61 +609,158 @@ impl Ice {
              * Some service actions *
              ************************/
             Message::UpdateCTime => {
-                self.ctime = chrono::offset::Local::now();
-                Task::none()
+                let now = chrono::offset::Local::now();
+                let woken = (now - self.ctime).num_seconds() > Self::WAKE_GAP;
+                self.ctime = now;
+                self.notifications.expire(self.ctime);
+                self.expire_weather_requests();
+
+                if (woken || self.location_outdated()) && self.location.is_some() {
+                    Task::done(Message::RedetectLocation)
+                } else if self.weather_due() {
+                    Task::done(Message::GetCurrentWeather)
+                } else {
+                    Task::none()
+                }
             }
             Message::UpdateUptime => {
                 self.uptime += 1;
                 Task::none()
             }
-            _ => Task::none(),
+            Message::NetworkChanged(change) => self.network_changed(change),
+            Message::Provisioning(status) => {
+                if matches!(status, provision::Status::Hotspot { .. }) {
+                    self.modal = Some(modal::Modal::Provisioning);
+                }
+                self.provisioning = Some(status);
+                Task::none()
+            }
+            Message::Provisioned(error, warning) => {
+                self.provisioning = None;
+                if self.modal == Some(modal::Modal::Provisioning) {
+                    self.modal = None;
+                }
+                if let Some(error) = error {
+                    self.notify(
+                        Notification::new(Topic::Provisioning, Severity::Error, error)
+                            .action(Action::Network),
+                    );
+                    return Task::none();
+                }
+                self.notify(match warning {
+                    Some(warning) => Notification::new(
+                        Topic::Provisioning,
+                        Severity::Warning,
+                        tr!("provisioning-no-city", error = warning),
+                    )
+                    .action(Action::Settings),
+                    None => Notification::new(
+                        Topic::Provisioning,
+                        Severity::Info,
+                        tr!("provisioning-done"),
+                    ),
+                });
+
+                // Location and API key may have been changed
+                match Config::read(CONF_PATH) {
+                    Ok(conf) => self.conf = conf,
+                    Err(why) => self.notify(
+                        Notification::new(Topic::Config, Severity::Error, why.to_string())
+                            .action(Action::Settings),
+                    ),
+                }
+                i18n::set_language(self.conf.interface.language);
+                self.forget_location();
+                self.current_weather = None;
+                self.daily_weather = None;
+                Task::none()
+            }
+
+            /*****************
+             * Notifications *
+             *****************/
+            Message::DismissNotification(topic) => {
+                self.notifications.clear(topic);
+                Task::none()
+            }
+            Message::NotificationAction(topic, action) => {
+                self.notifications.clear(topic);
+                Task::done(action.message())
+            }
+            Message::RefreshButtonPressed => match self.location {
+                Some(_) => Task::done(Message::GetCurrentWeather),
+                // The manual refresh doesn't wait for the retry delay
+                None => Task::done(Message::RedetectLocation),
+            },
+            Message::SampleSystem => self.sample_system(),
+            Message::SystemSampled(snapshot) => self.system_sampled(snapshot),
         }
     }
 
-    pub fn view(&self) -> Element<Message> {
-        let image = container(center(
+    pub fn view(&self) -> Element<'_, Message> {
+        // Seconds since the last weather update
+        let age = self
+            .weather_updated
+            .map(|time| (self.ctime - time).num_seconds());
+        // Highlighted if the weather has been missed twice
+        let stale = age.is_none_or(|age| age > 2 * self.refresh_interval());
+        let refreshing = self.weather_pending || self.location_pending;
+
+        let top_panel = row![
+            button(
+                text(if refreshing {
+                    tr!("refreshing")
+                } else {
+                    tr!("refresh")
+                })
+                .size(Self::TOP_PANEL_TEXT_SIZE)
+            )
+            .on_press_maybe((!refreshing).then_some(Message::RefreshButtonPressed)),
             column![
-                text(match &self.current_weather {
-                    Some(current) => current.weather[0].get_descr(),
-                    None => "Загружаем инф-цию...",
+                text(match (&self.location, &self.current_weather) {
+                    (
+                        Some(location::Resolved {
+                            name: Some(name), ..
+                        }),
+                        _,
+                    ) => name.clone(),
+                    (_, Some(current)) => format!("{} ({})", &current.name, &current.sys.country),
+                    _ => tr!("loading"),
                 })
-                .size(Self::TEXT_SIZE),
-                image(format!(
-                    "./res/icons/{}.png",
-                    match &self.current_weather {
-                        Some(current) => current.weather[0].get_icon(self.ctime),
-                        None => "default",
-                    }
-                )),
-                text(format!(
-                    "{}{}",
-                    floor(match &self.current_weather {
-                        Some(current) => current.main.feels_like,
-                        None => 0.,
+                .size(Self::TOP_PANEL_TEXT_SIZE),
+                row![
+                    text(match &self.location {
+                        Some(loc) => format!("[{}]", loc.source),
+                        None => String::new(),
+                    })
+                    .size(12),
+                    text(match age {
+                        Some(age) => tr!("weather-updated", ago = crate::time::ago(age)),
+                        None => tr!("weather-no-data"),
+                    })
+                    .size(12)
+                    .style(move |theme: &Theme| text::Style {
+                        color: stale.then_some(theme.extended_palette().danger.base.color),
                     }),
-                    Variant::Degrees.to_str(self.conf.units),
-                ))
-                .size(35)
+                ]
+                .spacing(5),
+            ],
+            horizontal_space(),
+            column![
+                widgets::connectivity(self.connectivity),
+                match self.wifi_signal {
+                    Some(signal) => widgets::signal_bars(signal),
+                    None => text("").size(12).into(),
+                },
             ]
-            .spacing(10)
+            .spacing(3)
             .align_x(Center),
-        ))
-        .padding(10)
-        .width(260);
-
-        let top_panel = row![
-            button(text("Обновить").size(Self::TOP_PANEL_TEXT_SIZE))
-                .on_press(Message::RefreshButtonPressed),
-            text(match &self.current_weather {
-                Some(current) => format!("{} ({})", &current.name, &current.sys.country,),
-                None => "Загружаем информацию...".to_string(),
-            })
-            .size(Self::TOP_PANEL_TEXT_SIZE),
             horizontal_space(),
             container(
                 text(
                     crate::time::Time::new(&self.ctime)
                         .set_display_mode(crate::time::DisplayMode::TimeDate)
+                        .set_format(self.conf.interface.time_format)
                         .to_string()
                 )
                 .size(Self::TOP_PANEL_TEXT_SIZE),
@@ -249,30 +772,86 @@ impl Ice {
         .spacing(10)
         .padding(10);
 
-        container(column![
+        let navigation = match self.page {
+            Page::Weather => row![
+                button(text(tr!("nav-location")).size(15))
+                    .on_press(Message::ShowModal(modal::Modal::Location)),
+                button(text(tr!("nav-forecast")).size(15)).on_press(Message::ShowPage(Page::Daily)),
+                button(text(tr!("nav-network")).size(15))
+                    .on_press(Message::ShowPage(Page::Network)),
+                button(text(tr!("nav-system")).size(15)).on_press(Message::ShowPage(Page::System)),
+                button(image("./res/icons/settings.png").width(18).height(18))
+                    .on_press(Message::ShowPage(Page::Settings)),
+                button(image("./res/icons/about.png").width(18).height(18))
+                    .on_press(Message::ShowModal(modal::Modal::About)),
+                button(image("./res/icons/power_off.png").width(18).height(18))
+                    .on_press(Message::ShowModal(modal::Modal::Power)),
+            ],
+            _ => row![
+                button(text(tr!("nav-back")).size(15)).on_press(Message::ShowPage(Page::Weather))
+            ],
+        }
+        .spacing(10);
+
+        let content = container(column![
             top_panel,
+            container(match self.page {
+                Page::Weather => self.weather_page(),
+                Page::Current => self.current_page(),
+                Page::Daily => self.daily_page(),
+                Page::Network => self.network_page(),
+                Page::System => self.system_page(),
+                Page::Settings => self.settings_page(),
+            })
+            .height(Length::Fill),
             row![
-                image,
-                center(scrollable(
-                    text("Тут должен быть почасовой прогноз").size(25),
-                ))
-            ]
-            .spacing(Self::TEXT_SIZE)
-            .align_y(Center),
-            row![
-                text("Информация предоставлена OpenWeatherMap").size(12),
+                navigation,
+                text(tr!("weather-attribution")).size(12),
                 horizontal_space(),
-                text(format!(
-                    "Время работы: {}",
-                    crate::time::Time::new(
+                text(tr!(
+                    "uptime",
+                    time = crate::time::Time::new(
                         &DateTime::from_timestamp(self.uptime.into(), 0).unwrap()
                     )
                     .set_display_mode(crate::time::DisplayMode::TimeWithSeconds)
+                    .to_string()
                 ))
                 .size(12),
             ]
+            .align_y(Center)
+            .spacing(10)
             .padding(10),
-        ])
-        .into()
+        ]);
+
+        let screen = match self.modal {
+            Some(modal::Modal::Location) => {
+                modal::modal(content, self.location_modal(), Message::HideModal)
+            }
+            Some(modal::Modal::Provisioning) => {
+                modal::modal(content, self.provisioning_modal(), Message::HideModal)
+            }
+            Some(modal::Modal::Power) => {
+                modal::modal(content, self.power_modal(), Message::HideModal)
+            }
+            Some(modal::Modal::About) => {
+                modal::modal(content, self.about_modal(), Message::HideModal)
+            }
+            Some(modal::Modal::PowerConfirm(action)) => modal::modal(
+                content,
+                self.power_confirm_modal(action),
+                Message::ShowModal(modal::Modal::Power),
+            ),
+            None => content.into(),
+        };
+        let screen = stack![screen, self.toasts()];
+
+        match self.notifications.error() {
+            Some(error) => modal::modal(
+                screen,
+                self.error_modal(error),
+                Message::DismissNotification(error.topic),
+            ),
+            None => screen.into(),
+        }
     }
 }
@@ -0,0 +1,184 @@
+//! Current conditions page
+
+use iced::{
+    Alignment::Center,
+    Element, Length,
+    widget::{
+        center, column, container, image, row, scrollable,
+        scrollable::{Direction, Scrollbar},
+        text,
+    },
+};
+
+use super::{Ice, Message, widgets};
+use crate::{
+    api::{current::Current, floor},
+    i18n::tr,
+    time::{DisplayMode, Time},
+    units::Variant,
+};
+
+/// hPa to mmHg
+const MMHG_PER_HPA: f32 = 0.750_062;
+
+impl Ice {
+    pub(super) fn current_page(&self) -> Element<'_, Message> {
+        let Some(current) = &self.current_weather else {
+            return center(text(tr!("loading")).size(Self::TEXT_SIZE)).into();
+        };
+        let units = self.conf.units;
+        let degrees = Variant::Degrees.to_str(units);
+        let speed = Variant::Speed.to_str(units);
+        let time = |time| {
+            Time::new(&time)
+                .set_format(self.conf.interface.time_format)
+                .to_string()
+        };
+
+        let meta = &current.weather[0];
+        let header = row![
+            image(format!("./res/icons/{}.png", meta.get_icon(self.ctime)))
+                .width(120)
+                .height(120),
+            column![
+                text(format!("{}{degrees}", floor(current.main.temp))).size(50),
+                text(tr!(
+                    "current-feels-like",
+                    temp = format!("{}{degrees}", floor(current.main.feels_like))
+                ))
+                .size(Self::TEXT_SIZE),
+                text(meta.get_descr()).size(Self::TEXT_SIZE),
+            ]
+            .spacing(5),
+        ]
+        .spacing(20)
+        .align_y(Center);
+
+        let main = &current.main;
+        let wind = &current.wind;
+        let gust = wind
+            .gust
+            .map(|gust| tr!("current-gust", speed = format!("{} {speed}", floor(gust))))
+            .unwrap_or_default();
+
+        let tiles = column![
+            row![
+                tile(
+                    tr!("current-min-max"),
+                    format!(
+                        "{}{degrees} / {}{degrees}",
+                        floor(main.temp_min),
+                        floor(main.temp_max)
+                    ),
+                    String::new(),
+                ),
+                tile(
+                    tr!("current-pressure"),
+                    tr!("unit-hpa", pressure = main.pressure),
+                    tr!(
+                        "unit-mmhg",
+                        pressure = (main.pressure as f32 * MMHG_PER_HPA).round()
+                    ),
+                ),
+                tile(
+                    tr!("current-humidity"),
+                    format!("{}%", main.humidity),
+                    String::new()
+                ),
+            ]
+            .spacing(10),
+            row![
+                tile(
+                    tr!("current-visibility"),
+                    visibility(current),
+                    String::new()
+                ),
+                tile(
+                    tr!("current-clouds"),
+                    current
+                        .clouds
+                        .as_ref()
+                        .map(|clouds| format!("{}%", clouds.all))
+                        .unwrap_or_else(|| "—".to_string()),
+                    String::new(),
+                ),
+                container(
+                    row![
+                        widgets::compass(wind, units, 64),
+                        column![
+                            text(tr!("current-wind")).size(15),
+                            text(format!(
+                                "{} {speed} {}",
+                                floor(wind.speed),
+                                wind.direction()
+                            ))
+                            .size(Self::TEXT_SIZE),
+                            text(gust).size(12),
+                        ]
+                        .spacing(3),
+                    ]
+                    .spacing(10)
+                    .align_y(Center),
+                )
+                .width(Length::Fill)
+                .padding(8)
+                .style(container::rounded_box),
+            ]
+            .spacing(10),
+            row![
+                tile(
+                    tr!("current-sunrise"),
+                    time(current.sunrise()),
+                    String::new()
+                ),
+                tile(tr!("current-sunset"), time(current.sunset()), String::new()),
+                tile(
+                    tr!("current-observed"),
+                    time(current.get_time()),
+                    Time::new(&current.get_time())
+                        .set_display_mode(DisplayMode::TimeDate)
+                        .set_format(self.conf.interface.time_format)
+                        .to_string(),
+                ),
+            ]
+            .spacing(10),
+        ]
+        .spacing(10);
+
+        // The last row doesn't fit the 800x480 screen under the panels
+        scrollable(container(column![header, tiles].spacing(10)).padding([0, 10]))
+            .direction(Direction::Vertical(
+                Scrollbar::new().width(10).scroller_width(10),
+            ))
+            .height(Length::Fill)
+            .into()
+    }
+}
+
+/// Card with the name, the value and the optional details of the parameter
+fn tile<'a>(name: String, value: String, details: String) -> Element<'a, Message> {
+    container(
+        column![
+            text(name).size(15),
+            text(value).size(Ice::TEXT_SIZE),
+            text(details).size(12),
+        ]
+        .spacing(3),
+    )
+    .width(Length::Fill)
+    .padding(8)
+    .style(container::rounded_box)
+    .into()
+}
+
+/// Visibility in km (OWM reports at most 10 km)
+fn visibility(current: &Current) -> String {
+    match current.visibility {
+        Some(meters) if meters >= 10_000 => tr!("current-visibility-max"),
+        Some(meters) => tr!(
+            "unit-km",
+            distance = format!("{:.1}", meters as f32 / 1000.)
+        ),
+        None => "—".to_string(),
+    }
+}
@@ -0,0 +1,178 @@
+//! Daily forecast page
+
+use chrono::{Datelike, FixedOffset, NaiveDate, Weekday};
+use iced::{
+    Alignment::Center,
+    Element, Length,
+    widget::{
+        button, center, column, container, horizontal_space, image, row, scrollable,
+        scrollable::{Direction, Scrollbar},
+        text,
+    },
+};
+
+use super::{Ice, Message, widgets};
+use crate::{
+    api::{
+        Wind,
+        daily::{Daily, Day},
+        floor,
+    },
+    i18n::tr,
+    time::Time,
+    units::{Units, Variant},
+};
+
+impl Ice {
+    pub(super) fn daily_page(&self) -> Element<'_, Message> {
+        let Some(daily) = &self.daily_weather else {
+            return center(text(tr!("daily-loading")).size(Self::TEXT_SIZE)).into();
+        };
+        // Days are counted in the location timezone, not the device one
+        let today = FixedOffset::east_opt(daily.city.timezone)
+            .map(|tz| self.ctime.with_timezone(&tz).date_naive())
+            .unwrap_or_else(|| self.ctime.date_naive());
+
+        let cards = column(
+            daily
+                .days()
+                .into_iter()
+                .map(|day| self.day_card(daily, day, today)),
+        )
+        .spacing(10)
+        .padding([0, 15]);
+
+        // The wide scroller is easy to grab with the finger, the list
+        // itself is scrolled by dragging
+        scrollable(cards)
+            .direction(Direction::Vertical(
+                Scrollbar::new().width(10).scroller_width(10),
+            ))
+            .height(Length::Fill)
+            .into()
+    }
+
+    fn day_card<'a>(&self, daily: &'a Daily, day: Day, today: NaiveDate) -> Element<'a, Message> {
+        let units = self.conf.units;
+        let degrees = Variant::Degrees.to_str(units);
+        let expanded = self.expanded_day == Some(day.date);
+        let main = &daily.list[day.main_entry];
+
+        let summary = row![
+            column![
+                text(day_name(day.date, today)).size(Self::TEXT_SIZE),
+                text(format!("{:02}.{:02}", day.date.day(), day.date.month())).size(12),
+            ]
+            .width(110),
+            image(format!(
+                "./res/icons/{}.png",
+                main.weather[0].get_icon(daily.get_time(day.main_entry))
+            ))
+            .width(48)
+            .height(48),
+            text(format!(
+                "{}{degrees} / {}{degrees}",
+                floor(day.temp_min),
+                floor(day.temp_max)
+            ))
+            .size(Self::TEXT_SIZE)
+            .width(130),
+            text(precipitation(day.pop, day.precipitation))
+                .size(15)
+                .width(160),
+            widgets::compass(&day.wind, units, 24),
+            text(wind(&day.wind, units)).size(15),
+            horizontal_space(),
+            // Only the button toggles the card, so the drag over the card
+            // scrolls the list without expanding it
+            button(text(if expanded { "▲" } else { "▼" }).size(Self::TEXT_SIZE))
+                .on_press(Message::ToggleDay(day.date))
+                .style(button::secondary)
+                .padding([8, 16]),
+        ]
+        .spacing(10)
+        .align_y(Center);
+
+        let details = expanded.then(|| {
+            column(day.entries.iter().map(|&idx| {
+                let data = &daily.list[idx];
+                let time = daily.get_time(idx);
+                row![
+                    text(
+                        Time::new(&time)
+                            .set_format(self.conf.interface.time_format)
+                            .to_string()
+                    )
+                    .size(15)
+                    .width(110),
+                    image(format!(
+                        "./res/icons/{}.png",
+                        data.weather[0].get_icon(time)
+                    ))
+                    .width(32)
+                    .height(32),
+                    text(format!("{}{degrees}", floor(data.main.temp)))
+                        .size(15)
+                        .width(60),
+                    text(data.weather[0].get_descr()).size(15).width(200),
+                    text(precipitation(data.pop, data.precipitation())).size(15),
+                    horizontal_space(),
+                    widgets::compass(&data.wind, units, 20),
+                    text(wind(&data.wind, units)).size(15),
+                ]
+                .spacing(10)
+                .align_y(Center)
+                .into()
+            }))
+            .spacing(3)
+        });
+
+        container(column![summary].push_maybe(details).spacing(10))
+            .width(Length::Fill)
+            .padding(10)
+            .style(container::rounded_box)
+            .into()
+    }
+}
+
+/// `Today`, `Tomorrow` or the day of the week
+fn day_name(date: NaiveDate, today: NaiveDate) -> String {
+    if date == today {
+        return tr!("daily-today");
+    }
+    if Some(date) == today.succ_opt() {
+        return tr!("daily-tomorrow");
+    }
+    tr!(match date.weekday() {
+        Weekday::Mon => "weekday-mon",
+        Weekday::Tue => "weekday-tue",
+        Weekday::Wed => "weekday-wed",
+        Weekday::Thu => "weekday-thu",
+        Weekday::Fri => "weekday-fri",
+        Weekday::Sat => "weekday-sat",
+        Weekday::Sun => "weekday-sun",
+    })
+}
+
+/// Probability and volume of the precipitation (`Precipitation 60%, 2.5 mm`)
+fn precipitation(pop: f32, volume: f32) -> String {
+    let pop = (pop * 100.).round();
+    if volume > 0. {
+        tr!(
+            "daily-precipitation-volume",
+            pop = pop,
+            volume = format!("{volume:.1}")
+        )
+    } else {
+        tr!("daily-precipitation", pop = pop)
+    }
+}
+
+fn wind(wind: &Wind, units: Units) -> String {
+    format!(
+        "{} {} {}",
+        floor(wind.speed),
+        Variant::Speed.to_str(units),
+        wind.direction()
+    )
+}
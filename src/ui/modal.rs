@@ -0,0 +1,414 @@
+//! Some modal windows (location selector, about program, error windows)
+
+use iced::{
+    Alignment::Center,
+    Color, Element, Length,
+    widget::{
+        button, center, column, container, horizontal_space, image, mouse_area, opaque, row,
+        scrollable, stack, text, text_input,
+    },
+};
+
+use super::{
+    Ice, Message,
+    notification::{self, Notification},
+};
+use crate::{
+    app::{geoip, power, provision},
+    consts::{BUILD_DATE, GIT_COMMIT, PROG_AUTHOR, PROG_NAME, PROG_VER},
+    i18n::tr,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Modal {
+    /// Location search and selection
+    Location,
+
+    /// Instructions for the setup via the Wi-Fi hotspot
+    Provisioning,
+
+    /// Shutdown, reboot and exit to the console
+    Power,
+
+    /// Confirmation of the power menu action
+    PowerConfirm(power::Action),
+
+    /// Version, device information and licences
+    About,
+}
+
+/// Authors of the icons (`| Icons | Original URL |` table rows)
+fn icon_credits() -> impl Iterator<Item = (&'static str, &'static str)> {
+    include_str!("../../res/icons/CREDITS.md")
+        .lines()
+        .filter_map(|line| {
+            let mut cells = line.trim().strip_prefix('|')?.split('|').map(str::trim);
+            Some((cells.next()?, cells.next()?))
+        })
+        // Header and separator
+        .skip(2)
+}
+
+/// Label of the power menu button
+fn power_label(action: power::Action) -> String {
+    tr!(match action {
+        power::Action::PowerOff => "power-off",
+        power::Action::Reboot => "power-reboot",
+        power::Action::ExitToConsole => "power-exit",
+    })
+}
+
+/// Shows `content` over the `base` element. Pressing outside of the
+/// `content` sends `on_blur` message
+pub fn modal<'a>(
+    base: impl Into<Element<'a, Message>>,
+    content: impl Into<Element<'a, Message>>,
+    on_blur: Message,
+) -> Element<'a, Message> {
+    stack![
+        base.into(),
+        opaque(
+            mouse_area(center(opaque(content)).style(|_theme| {
+                container::Style {
+                    background: Some(
+                        Color {
+                            a: 0.8,
+                            ..Color::BLACK
+                        }
+                        .into(),
+                    ),
+                    ..container::Style::default()
+                }
+            }))
+            .on_press(on_blur)
+        )
+    ]
+    .into()
+}
+
+impl Ice {
+    const MODAL_WIDTH: u16 = 600;
+
+    pub(super) fn location_modal(&self) -> Element<'_, Message> {
+        let search = row![
+            text_input(&tr!("location-search-placeholder"), &self.location_query)
+                .on_input(Message::LocationQueryChanged)
+                .on_submit(Message::SearchLocation)
+                .size(Self::TEXT_SIZE)
+                .padding(10),
+            button(text(tr!("location-search")).size(Self::TEXT_SIZE))
+                .on_press(Message::SearchLocation)
+                .padding(10),
+        ]
+        .spacing(10)
+        .align_y(Center);
+
+        let found = match &self.geocoding {
+            Some(geo) if geo.0.is_empty() => {
+                column![text(tr!("location-not-found")).size(Self::TEXT_SIZE)]
+            }
+            Some(geo) => column(geo.0.iter().map(|loc| {
+                let selected = self.selected_location.as_ref() == Some(loc);
+                button(text(loc.to_string()).size(Self::TEXT_SIZE))
+                    .on_press(Message::LocationSelected(loc.clone()))
+                    .style(if selected {
+                        button::primary
+                    } else {
+                        button::secondary
+                    })
+                    .width(Length::Fill)
+                    .padding(10)
+                    .into()
+            }))
+            .spacing(5),
+            None => column![],
+        };
+
+        container(
+            column![
+                row![
+                    text(tr!("nav-location")).size(Self::TOP_PANEL_TEXT_SIZE),
+                    horizontal_space(),
+                    button(text(tr!("close")).size(Self::TEXT_SIZE)).on_press(Message::HideModal),
+                ]
+                .align_y(Center),
+                search,
+                scrollable(found).height(250),
+            ]
+            .spacing(10),
+        )
+        .width(Self::MODAL_WIDTH)
+        .padding(20)
+        .style(container::rounded_box)
+        .into()
+    }
+
+    pub(super) fn provisioning_modal(&self) -> Element<'_, Message> {
+        let body = match &self.provisioning {
+            Some(provision::Status::Hotspot {
+                ssid,
+                password,
+                url,
+            }) => column![
+                text(tr!("provisioning-connect")).size(Self::TEXT_SIZE),
+                text(ssid).size(Self::TOP_PANEL_TEXT_SIZE),
+                text(tr!("provisioning-password", password = password.as_str()))
+                    .size(Self::TOP_PANEL_TEXT_SIZE),
+                text(tr!("provisioning-open-page")).size(Self::TEXT_SIZE),
+                text(url).size(Self::TOP_PANEL_TEXT_SIZE),
+            ],
+            Some(provision::Status::Connecting(ssid)) => {
+                column![
+                    text(tr!("provisioning-connecting", ssid = ssid.as_str()))
+                        .size(Self::TEXT_SIZE)
+                ]
+            }
+            _ => column![],
+        };
+
+        container(
+            column![
+                row![
+                    text(tr!("provisioning-title")).size(Self::TOP_PANEL_TEXT_SIZE),
+                    horizontal_space(),
+                    button(text(tr!("close")).size(Self::TEXT_SIZE)).on_press(Message::HideModal),
+                ]
+                .align_y(Center),
+                body.spacing(10).align_x(Center),
+            ]
+            .spacing(10),
+        )
+        .width(Self::MODAL_WIDTH)
+        .padding(20)
+        .style(container::rounded_box)
+        .into()
+    }
+
+    pub(super) fn power_modal(&self) -> Element<'_, Message> {
+        let actions = column(power::Action::ALL.into_iter().map(|action| {
+            // Actions which can't be checked (e.g. there is no logind) are
+            // tried anyway
+            let permission = self
+                .power_permissions
+                .iter()
+                .find(|(other, _)| *other == action)
+                .map(|(_, permission)| *permission);
+            let hint = match permission {
+                Some(power::Permission::Challenge | power::Permission::Denied) => {
+                    Some(tr!("power-denied"))
+                }
+                Some(power::Permission::Unsupported) => Some(tr!("power-unsupported")),
+                _ => None,
+            };
+
+            column![
+                button(text(power_label(action)).size(Self::TEXT_SIZE).center())
+                    .on_press_maybe(
+                        hint.is_none()
+                            .then_some(Message::ShowModal(Modal::PowerConfirm(action)))
+                    )
+                    .style(if action == power::Action::PowerOff {
+                        button::danger
+                    } else {
+                        button::primary
+                    })
+                    .width(Length::Fill)
+                    .padding(10),
+            ]
+            .push_maybe(hint.map(|hint| text(hint).size(12)))
+            .spacing(3)
+            .align_x(Center)
+            .into()
+        }))
+        .spacing(10);
+
+        container(
+            column![
+                row![
+                    text(tr!("power-title")).size(Self::TOP_PANEL_TEXT_SIZE),
+                    horizontal_space(),
+                    button(text(tr!("close")).size(Self::TEXT_SIZE)).on_press(Message::HideModal),
+                ]
+                .align_y(Center),
+                actions,
+            ]
+            .push_maybe(
+                self.power_status
+                    .as_ref()
+                    .map(|status| text(status).size(Self::TEXT_SIZE).style(text::danger)),
+            )
+            .spacing(10),
+        )
+        .width(Self::MODAL_WIDTH / 2)
+        .padding(20)
+        .style(container::rounded_box)
+        .into()
+    }
+
+    pub(super) fn power_confirm_modal(&self, action: power::Action) -> Element<'_, Message> {
+        let question = tr!(match action {
+            power::Action::PowerOff => "power-off-confirm",
+            power::Action::Reboot => "power-reboot-confirm",
+            power::Action::ExitToConsole => "power-exit-confirm",
+        });
+
+        container(
+            column![
+                text(question).size(Self::TOP_PANEL_TEXT_SIZE),
+                row![
+                    button(text(tr!("cancel")).size(Self::TEXT_SIZE))
+                        .on_press(Message::ShowModal(Modal::Power))
+                        .style(button::secondary)
+                        .padding(10),
+                    button(text(power_label(action)).size(Self::TEXT_SIZE))
+                        .on_press(Message::PowerConfirmed(action))
+                        .style(button::danger)
+                        .padding(10),
+                ]
+                .spacing(10),
+            ]
+            .spacing(20)
+            .align_x(Center),
+        )
+        .padding(20)
+        .style(container::rounded_box)
+        .into()
+    }
+
+    pub(super) fn about_modal(&self) -> Element<'_, Message> {
+        let line = |name: String, value: String| {
+            row![
+                text(name).size(15).width(150),
+                text(value).size(15).width(Length::Fill),
+            ]
+            .spacing(10)
+        };
+        let unknown = || tr!("about-unknown");
+
+        let mut device = column![].spacing(3);
+        if let Some(info) = &self.about {
+            device = device
+                .push(line(
+                    tr!("about-device"),
+                    info.model.clone().unwrap_or_else(unknown),
+                ))
+                .push(line(
+                    tr!("about-os"),
+                    info.os.clone().unwrap_or_else(unknown),
+                ))
+                .push(line(
+                    tr!("about-kernel"),
+                    info.kernel.clone().unwrap_or_else(unknown),
+                ))
+                .push(line(
+                    tr!("about-addresses"),
+                    if info.addresses.is_empty() {
+                        tr!("none")
+                    } else {
+                        info.addresses
+                            .iter()
+                            .map(|addr| format!("{} ({})", addr.ip, addr.interface))
+                            .collect::<Vec<_>>()
+                            .join("\n")
+                    },
+                ));
+        }
+
+        let mut sources = column![
+            line(
+                tr!("about-weather"),
+                "OpenWeatherMap (openweathermap.org), CC BY-SA 4.0".to_string()
+            ),
+            line(
+                tr!("about-cities"),
+                "GeoNames (geonames.org), CC BY 4.0".to_string()
+            ),
+            line(
+                tr!("nav-location"),
+                self.conf
+                    .geoip
+                    .iter()
+                    .map(|provider| provider.name())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ),
+        ]
+        .spacing(3);
+        if self
+            .conf
+            .geoip
+            .iter()
+            .any(|provider| matches!(provider, geoip::Provider::MaxMind(_)))
+        {
+            sources = sources.push(line(
+                String::new(),
+                "This product includes GeoLite2 data created by MaxMind, available from maxmind.com"
+                    .to_string(),
+            ));
+        }
+        for (icons, url) in icon_credits() {
+            sources = sources.push(line(tr!("about-icons"), format!("{icons}: {url}")));
+        }
+
+        container(
+            column![
+                row![
+                    image("./res/icons/about.png").width(32).height(32),
+                    text(format!("{PROG_NAME} {PROG_VER}")).size(Self::TOP_PANEL_TEXT_SIZE),
+                    horizontal_space(),
+                    button(text(tr!("close")).size(Self::TEXT_SIZE)).on_press(Message::HideModal),
+                ]
+                .spacing(10)
+                .align_y(Center),
+                scrollable(
+                    column![
+                        line(
+                            tr!("about-build"),
+                            tr!("about-build-info", commit = GIT_COMMIT, date = BUILD_DATE),
+                        ),
+                        line(tr!("about-license"), "MIT".to_string()),
+                        text(PROG_AUTHOR).size(15),
+                        device,
+                        text(tr!("about-sources")).size(Self::TEXT_SIZE),
+                        sources,
+                    ]
+                    .spacing(10),
+                )
+                .height(300),
+            ]
+            .spacing(10),
+        )
+        .width(Self::MODAL_WIDTH + 100)
+        .padding(20)
+        .style(container::rounded_box)
+        .into()
+    }
+
+    pub(super) fn error_modal<'a>(&self, error: &'a Notification) -> Element<'a, Message> {
+        container(
+            column![
+                row![
+                    text(tr!("error-title")).size(Self::TOP_PANEL_TEXT_SIZE),
+                    horizontal_space(),
+                    text(notification::timestamp(error)).size(15),
+                ]
+                .align_y(Center),
+                scrollable(text(&error.text).size(Self::TEXT_SIZE)).height(Length::Shrink),
+                row![
+                    notification::actions(error, Self::TEXT_SIZE),
+                    horizontal_space(),
+                    button(text(tr!("close")).size(Self::TEXT_SIZE))
+                        .on_press(Message::DismissNotification(error.topic))
+                        .style(button::secondary),
+                ]
+                .align_y(Center),
+            ]
+            .spacing(10),
+        )
+        .width(Self::MODAL_WIDTH)
+        .max_height(400)
+        .padding(20)
+        .style(container::rounded_box)
+        .into()
+    }
+}
@@ -0,0 +1,746 @@
+//! Network page
+
+use iced::{
+    Alignment::Center,
+    Element, Length, Task,
+    futures::{SinkExt, Stream, StreamExt},
+    stream,
+    widget::{
+        Space, button, column, container, horizontal_space, image, row, scrollable, text,
+        text_input,
+    },
+};
+
+use std::path::Path;
+
+use super::{
+    Ice, Message,
+    widgets::{self, Key, KeyboardMode},
+};
+use crate::{
+    app::{
+        network::{self, AccessPoint, ConnectStatus, ConnectionInfo, SavedNetwork, Security},
+        provision,
+    },
+    config::Config,
+    consts::CONF_PATH,
+    i18n::tr,
+};
+
+/// Forwards NetworkManager state changes to the UI. Does nothing if the
+/// D-Bus or NetworkManager is unavailable
+pub fn watch_network() -> impl Stream<Item = Message> {
+    stream::channel(10, |mut output| async move {
+        if let Ok(changes) = network::network_changes().await {
+            let mut changes = std::pin::pin!(changes);
+            while let Some(change) = changes.next().await {
+                let _ = output.send(Message::NetworkChanged(change)).await;
+            }
+        }
+        std::future::pending::<()>().await;
+    })
+}
+
+/// Starts the setup via the Wi-Fi hotspot if there is no connection at
+/// startup (see [`provision::run`])
+pub fn provision() -> impl Stream<Item = Message> {
+    stream::channel(10, |mut output| async move {
+        // Only the provisioning settings are used if the config is broken,
+        // it's not written then
+        let original = Config::read(CONF_PATH).unwrap_or_default();
+        let mut conf = original.clone();
+        let mut report = output.clone();
+        let result = provision::run(&mut conf, |status| {
+            let _ = report.try_send(Message::Provisioning(status));
+        })
+        .await;
+
+        let (error, warning) = match result {
+            Ok(provision::Outcome::Provisioned { warning }) => (
+                provision::save(&original, &conf, Path::new(CONF_PATH))
+                    .err()
+                    .map(|why| why.to_string()),
+                warning,
+            ),
+            Err(why) => (Some(why.to_string()), None),
+            // The device is online and the config isn't changed
+            Ok(provision::Outcome::Online) => return std::future::pending().await,
+        };
+        let _ = output.send(Message::Provisioned(error, warning)).await;
+        std::future::pending::<()>().await;
+    })
+}
+
+/// List shown on the network page
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Tab {
+    /// Scanned networks
+    #[default]
+    Available,
+    Saved,
+}
+
+/// Text entry with the on-screen keyboard
+#[derive(Debug, Clone, PartialEq)]
+pub enum Entry {
+    /// Password of the new network
+    Password(AccessPoint),
+
+    /// New password of the saved network
+    ChangePassword(SavedNetwork),
+
+    /// SSID and password of the network which doesn't broadcast its SSID
+    Hidden,
+}
+
+/// Field of the hidden network form which receives the keys
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Field {
+    #[default]
+    Ssid,
+    Password,
+}
+
+/// State of the network page
+#[derive(Debug, Default)]
+pub struct NetworkPage {
+    tab: Tab,
+    networks: Vec<AccessPoint>,
+    saved: Vec<SavedNetwork>,
+    scanning: bool,
+    connection: Option<ConnectionInfo>,
+
+    entry: Option<Entry>,
+    field: Field,
+    ssid: String,
+    password: String,
+    keyboard: KeyboardMode,
+    connecting: bool,
+
+    /// Result of the last action
+    status: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum NetworkMessage {
+    ShowTab(Tab),
+
+    ScanWifi,
+    WifiScanned((Option<Vec<AccessPoint>>, Option<String>)),
+    GetSavedNetworks,
+    SavedNetworksReceived((Option<Vec<SavedNetwork>>, Option<String>)),
+    GetConnectionInfo,
+    ConnectionInfoReceived((Option<ConnectionInfo>, Option<String>)),
+
+    /*****************
+     * Text entering *
+     *****************/
+    WifiSelected(AccessPoint),
+    EditPassword(SavedNetwork),
+    HiddenNetwork,
+    FieldSelected(Field),
+    WifiSsidChanged(String),
+    WifiPasswordChanged(String),
+    WifiKey(Key),
+    EntryCancel,
+    EntrySubmit,
+
+    /***********
+     * Actions *
+     ***********/
+    ConnectSaved(SavedNetwork),
+    /// SSID and the result of the connection
+    WifiConnected(String, (Option<ConnectStatus>, Option<String>)),
+    DisconnectWifi,
+    ForgetNetwork(SavedNetwork),
+    /// New autoconnect flag and priority
+    SetAutoconnect(SavedNetwork, bool, i32),
+    /// Error of the finished action
+    ActionDone(Option<String>),
+}
+
+impl From<NetworkMessage> for Message {
+    fn from(message: NetworkMessage) -> Self {
+        Self::Network(message)
+    }
+}
+
+/// Runs the network action and reports its error
+fn action<F>(action: F) -> Task<Message>
+where
+    F: Future<Output = anyhow::Result<()>> + Send + 'static,
+{
+    Task::perform(
+        async move { action.await.err().map(|why| why.to_string()) },
+        |error| NetworkMessage::ActionDone(error).into(),
+    )
+}
+
+/// Runs the connection and reports its result
+fn connect<F>(ssid: String, connection: F) -> Task<Message>
+where
+    F: Future<Output = anyhow::Result<ConnectStatus>> + Send + 'static,
+{
+    Task::perform(
+        async move {
+            match connection.await {
+                Ok(status) => (Some(status), None),
+                Err(why) => (None, Some(why.to_string())),
+            }
+        },
+        move |result| NetworkMessage::WifiConnected(ssid.clone(), result).into(),
+    )
+}
+
+/// Refreshes everything shown on the page
+pub(super) fn refresh() -> Task<Message> {
+    Task::batch([
+        Task::done(NetworkMessage::GetConnectionInfo.into()),
+        Task::done(NetworkMessage::GetSavedNetworks.into()),
+        Task::done(NetworkMessage::ScanWifi.into()),
+    ])
+}
+
+impl Ice {
+    pub(super) fn update_network(&mut self, message: NetworkMessage) -> Task<Message> {
+        let page = &mut self.network;
+
+        match message {
+            NetworkMessage::ShowTab(tab) => {
+                page.tab = tab;
+                Task::none()
+            }
+
+            NetworkMessage::ScanWifi => {
+                if page.scanning {
+                    return Task::none();
+                }
+                page.scanning = true;
+
+                Task::perform(
+                    async {
+                        match network::scan_wifi().await {
+                            Ok(networks) => (Some(networks), None),
+                            Err(why) => (None, Some(why.to_string())),
+                        }
+                    },
+                    |result| NetworkMessage::WifiScanned(result).into(),
+                )
+            }
+            NetworkMessage::WifiScanned((networks, error)) => {
+                page.scanning = false;
+                if let Some(networks) = networks {
+                    page.networks = networks;
+                }
+                if error.is_some() {
+                    page.status = error;
+                }
+                Task::none()
+            }
+            NetworkMessage::GetSavedNetworks => Task::perform(
+                async {
+                    match network::saved_networks().await {
+                        Ok(saved) => (Some(saved), None),
+                        Err(why) => (None, Some(why.to_string())),
+                    }
+                },
+                |result| NetworkMessage::SavedNetworksReceived(result).into(),
+            ),
+            NetworkMessage::SavedNetworksReceived((saved, error)) => {
+                if let Some(saved) = saved {
+                    page.saved = saved;
+                }
+                if error.is_some() {
+                    page.status = error;
+                }
+                Task::none()
+            }
+            NetworkMessage::GetConnectionInfo => Task::perform(
+                async {
+                    match network::connection_info().await {
+                        Ok(info) => (info, None),
+                        Err(why) => (None, Some(why.to_string())),
+                    }
+                },
+                |result| NetworkMessage::ConnectionInfoReceived(result).into(),
+            ),
+            NetworkMessage::ConnectionInfoReceived((info, error)) => {
+                page.connection = info;
+                if error.is_some() {
+                    page.status = error;
+                }
+                Task::none()
+            }
+
+            /*****************
+             * Text entering *
+             *****************/
+            NetworkMessage::WifiSelected(ap) => {
+                // Known networks are connected with the saved password
+                if let Some(saved) = page.saved.iter().find(|saved| saved.ssid == ap.ssid) {
+                    return Task::done(NetworkMessage::ConnectSaved(saved.clone()).into());
+                }
+
+                let open = !ap.security.is_secured();
+                page.start_entry(Entry::Password(ap), Field::Password);
+
+                // Open networks don't need the password
+                if open {
+                    Task::done(NetworkMessage::EntrySubmit.into())
+                } else {
+                    Task::none()
+                }
+            }
+            NetworkMessage::EditPassword(saved) => {
+                page.start_entry(Entry::ChangePassword(saved), Field::Password);
+                Task::none()
+            }
+            NetworkMessage::HiddenNetwork => {
+                page.start_entry(Entry::Hidden, Field::Ssid);
+                Task::none()
+            }
+            NetworkMessage::FieldSelected(field) => {
+                page.field = field;
+                Task::none()
+            }
+            NetworkMessage::WifiSsidChanged(ssid) => {
+                page.ssid = ssid;
+                Task::none()
+            }
+            NetworkMessage::WifiPasswordChanged(password) => {
+                page.password = password;
+                Task::none()
+            }
+            NetworkMessage::WifiKey(key) => {
+                let field = match page.field {
+                    Field::Ssid => &mut page.ssid,
+                    Field::Password => &mut page.password,
+                };
+                if !key.apply(field, &mut page.keyboard) {
+                    Task::none()
+                } else if page.field == Field::Ssid {
+                    page.field = Field::Password;
+                    Task::none()
+                } else {
+                    Task::done(NetworkMessage::EntrySubmit.into())
+                }
+            }
+            NetworkMessage::EntryCancel => {
+                page.entry = None;
+                page.password.clear();
+                Task::none()
+            }
+            NetworkMessage::EntrySubmit => {
+                let Some(entry) = page.entry.clone() else {
+                    return Task::none();
+                };
+                if page.connecting {
+                    return Task::none();
+                }
+                let password = page.password.clone();
+
+                match entry {
+                    Entry::Password(ap) => {
+                        page.connecting = true;
+                        page.status = Some(tr!("network-connecting", ssid = ap.ssid.as_str()));
+
+                        connect(ap.ssid.clone(), async move {
+                            let pass = ap.security.is_secured().then_some(password.as_str());
+                            network::connect_wifi(&ap, pass).await
+                        })
+                    }
+                    Entry::Hidden => {
+                        let ssid = page.ssid.trim().to_string();
+                        page.connecting = true;
+                        page.status = Some(tr!("network-connecting", ssid = ssid.as_str()));
+
+                        connect(ssid.clone(), async move {
+                            let (security, pass) = if password.is_empty() {
+                                (Security::Open, None)
+                            } else {
+                                (Security::WpaPsk, Some(password.as_str()))
+                            };
+                            network::connect_hidden(&ssid, security, pass).await
+                        })
+                    }
+                    Entry::ChangePassword(saved) => {
+                        page.entry = None;
+                        page.password.clear();
+                        action(async move { network::set_password(&saved.path, &password).await })
+                    }
+                }
+            }
+
+            /***********
+             * Actions *
+             ***********/
+            NetworkMessage::ConnectSaved(saved) => {
+                if page.connecting {
+                    return Task::none();
+                }
+                page.connecting = true;
+                page.status = Some(tr!("network-connecting", ssid = saved.ssid.as_str()));
+
+                connect(saved.ssid.clone(), async move {
+                    network::activate_saved(&saved.path).await
+                })
+            }
+            NetworkMessage::WifiConnected(ssid, (status, error)) => {
+                page.connecting = false;
+                page.status = match (status, error) {
+                    (Some(ConnectStatus::Connected), _) => {
+                        page.entry = None;
+                        page.password.clear();
+                        Some(tr!("network-connected", ssid = ssid.as_str()))
+                    }
+                    (Some(ConnectStatus::WrongPassword), _) => {
+                        page.password.clear();
+                        Some(tr!("network-wrong-password"))
+                    }
+                    (Some(ConnectStatus::Timeout), _) => {
+                        Some(tr!("network-timeout", ssid = ssid.as_str()))
+                    }
+                    (Some(ConnectStatus::Failed(reason)), _) => {
+                        Some(tr!("network-failed", ssid = ssid.as_str(), reason = reason))
+                    }
+                    (None, error) => error,
+                };
+                refresh()
+            }
+            NetworkMessage::DisconnectWifi => {
+                let Some(info) = &page.connection else {
+                    return Task::none();
+                };
+                let path = info.path.clone();
+                action(async move { network::disconnect(&path).await })
+            }
+            NetworkMessage::ForgetNetwork(saved) => {
+                action(async move { network::forget_network(&saved.path).await })
+            }
+            NetworkMessage::SetAutoconnect(saved, autoconnect, priority) => {
+                action(
+                    async move { network::set_autoconnect(&saved.path, autoconnect, priority).await },
+                )
+            }
+            NetworkMessage::ActionDone(error) => {
+                page.status = error;
+                refresh()
+            }
+        }
+    }
+
+    pub(super) fn network_page(&self) -> Element<'_, Message> {
+        let page = &self.network;
+
+        // Text entry replaces the lists, so the keyboard fits the screen
+        let body = match &page.entry {
+            Some(entry) => self.network_entry(entry),
+            None => {
+                let connection = match &page.connection {
+                    Some(info) => container(
+                        row![
+                            column![
+                                text(&info.id).size(Self::TOP_PANEL_TEXT_SIZE),
+                                text(format!(
+                                    "IP: {}",
+                                    info.ip.clone().unwrap_or_else(|| tr!("none"))
+                                ))
+                                .size(Self::TEXT_SIZE),
+                                text(tr!(
+                                    "network-gateway",
+                                    gateway = info.gateway.clone().unwrap_or_else(|| tr!("none"))
+                                ))
+                                .size(Self::TEXT_SIZE),
+                            ]
+                            .spacing(5),
+                            horizontal_space(),
+                            button(text(tr!("network-disconnect")).size(Self::TEXT_SIZE))
+                                .on_press(NetworkMessage::DisconnectWifi.into())
+                                .style(button::danger)
+                                .padding(10),
+                        ]
+                        .align_y(Center),
+                    ),
+                    None => container(
+                        text(tr!("network-no-connection")).size(Self::TOP_PANEL_TEXT_SIZE),
+                    ),
+                }
+                .width(Length::Fill)
+                .padding(10)
+                .style(container::rounded_box);
+
+                let tab = |label: String, tab| {
+                    button(text(label).size(Self::TEXT_SIZE))
+                        .on_press(NetworkMessage::ShowTab(tab).into())
+                        .style(if page.tab == tab {
+                            button::primary
+                        } else {
+                            button::secondary
+                        })
+                };
+                let tabs = row![
+                    tab(tr!("network-available"), Tab::Available),
+                    tab(tr!("network-saved"), Tab::Saved),
+                    text(page.status.as_deref().unwrap_or_default()).size(15),
+                    horizontal_space(),
+                    match page.tab {
+                        Tab::Available => button(
+                            text(if page.scanning {
+                                tr!("network-scanning")
+                            } else {
+                                tr!("refresh")
+                            })
+                            .size(Self::TEXT_SIZE),
+                        )
+                        .on_press_maybe(
+                            (!page.scanning).then_some(NetworkMessage::ScanWifi.into()),
+                        ),
+                        Tab::Saved => button(text(tr!("network-hidden")).size(Self::TEXT_SIZE))
+                            .on_press(NetworkMessage::HiddenNetwork.into()),
+                    },
+                ]
+                .spacing(10)
+                .align_y(Center);
+
+                column![
+                    connection,
+                    tabs,
+                    scrollable(match page.tab {
+                        Tab::Available => self.available_networks(),
+                        Tab::Saved => self.saved_networks(),
+                    })
+                    .height(Length::Fill),
+                ]
+                .spacing(10)
+                .into()
+            }
+        };
+
+        container(body).padding([0, 10]).height(Length::Fill).into()
+    }
+
+    fn available_networks(&self) -> Element<'_, Message> {
+        column(self.network.networks.iter().map(|ap| {
+            button(
+                row![
+                    widgets::signal_bars(ap.strength),
+                    lock_icon(ap.security),
+                    text(&ap.ssid).size(Self::TEXT_SIZE),
+                    horizontal_space(),
+                    text(if ap.in_use {
+                        tr!("network-in-use")
+                    } else {
+                        tr!(
+                            "unit-ghz",
+                            frequency = format!("{:.1}", ap.frequency as f32 / 1000.)
+                        )
+                    })
+                    .size(15),
+                ]
+                .spacing(10)
+                .align_y(Center),
+            )
+            .on_press_maybe((!ap.in_use).then(|| NetworkMessage::WifiSelected(ap.clone()).into()))
+            .style(if ap.in_use {
+                button::primary
+            } else {
+                button::secondary
+            })
+            .width(Length::Fill)
+            .padding(10)
+            .into()
+        }))
+        .spacing(5)
+        .into()
+    }
+
+    fn saved_networks(&self) -> Element<'_, Message> {
+        const BUTTON_TEXT_SIZE: u16 = 15;
+
+        let page = &self.network;
+        if page.saved.is_empty() {
+            return text(tr!("network-no-saved")).size(Self::TEXT_SIZE).into();
+        }
+
+        column(page.saved.iter().map(|saved| {
+            let small = |label: String, message: NetworkMessage| {
+                button(text(label).size(BUTTON_TEXT_SIZE))
+                    .on_press(message.into())
+                    .style(button::secondary)
+            };
+            let priority = |priority: i32| {
+                NetworkMessage::SetAutoconnect(
+                    saved.clone(),
+                    saved.autoconnect,
+                    priority.clamp(
+                        *network::PRIORITY_RANGE.start(),
+                        *network::PRIORITY_RANGE.end(),
+                    ),
+                )
+            };
+            let connected = page
+                .connection
+                .as_ref()
+                .is_some_and(|info| info.id == saved.id);
+
+            let mut actions = row![
+                small("−".to_string(), priority(saved.priority - 1)),
+                text(saved.priority).size(BUTTON_TEXT_SIZE),
+                small("+".to_string(), priority(saved.priority + 1)),
+                small(
+                    if saved.autoconnect {
+                        tr!("network-autoconnect-on")
+                    } else {
+                        tr!("network-autoconnect-off")
+                    },
+                    NetworkMessage::SetAutoconnect(
+                        saved.clone(),
+                        !saved.autoconnect,
+                        saved.priority
+                    ),
+                ),
+            ]
+            .spacing(5)
+            .align_y(Center);
+            if saved.security.is_secured() {
+                actions = actions.push(small(
+                    tr!("network-password"),
+                    NetworkMessage::EditPassword(saved.clone()),
+                ));
+            }
+            if !connected {
+                actions = actions.push(small(
+                    tr!("network-connect-saved"),
+                    NetworkMessage::ConnectSaved(saved.clone()),
+                ));
+            }
+            actions = actions.push(
+                button(text(tr!("network-forget")).size(BUTTON_TEXT_SIZE))
+                    .on_press(NetworkMessage::ForgetNetwork(saved.clone()).into())
+                    .style(button::danger),
+            );
+
+            container(
+                row![
+                    lock_icon(saved.security),
+                    column![
+                        text(&saved.id).size(Self::TEXT_SIZE),
+                        text(match (connected, saved.hidden) {
+                            (true, _) => tr!("network-in-use"),
+                            (false, true) => tr!("network-hidden-short"),
+                            (false, false) => String::new(),
+                        })
+                        .size(12),
+                    ],
+                    horizontal_space(),
+                    actions,
+                ]
+                .spacing(10)
+                .align_y(Center),
+            )
+            .width(Length::Fill)
+            .padding(5)
+            .style(container::rounded_box)
+            .into()
+        }))
+        .spacing(5)
+        .into()
+    }
+
+    fn network_entry<'a>(&'a self, entry: &'a Entry) -> Element<'a, Message> {
+        let page = &self.network;
+
+        let field = |field: Field, label: String| {
+            button(text(label).size(Self::TEXT_SIZE))
+                .on_press(NetworkMessage::FieldSelected(field).into())
+                .style(if page.field == field {
+                    button::primary
+                } else {
+                    button::secondary
+                })
+                .padding(5)
+        };
+        let password = text_input(&tr!("network-password"), &page.password)
+            .on_input(|pass| NetworkMessage::WifiPasswordChanged(pass).into())
+            .on_submit(NetworkMessage::EntrySubmit.into())
+            .secure(true)
+            .size(Self::TEXT_SIZE)
+            .padding(5);
+
+        let (title, submit) = match entry {
+            Entry::Password(ap) => (ap.ssid.clone(), tr!("network-connect")),
+            Entry::ChangePassword(saved) => (
+                tr!("network-new-password", id = saved.id.as_str()),
+                tr!("settings-save"),
+            ),
+            Entry::Hidden => (tr!("network-hidden"), tr!("network-connect")),
+        };
+
+        let mut fields = column![].spacing(5);
+        if *entry == Entry::Hidden {
+            fields = fields
+                .push(
+                    row![
+                        field(Field::Ssid, tr!("network-ssid")),
+                        text_input("SSID", &page.ssid)
+                            .on_input(|ssid| NetworkMessage::WifiSsidChanged(ssid).into())
+                            .on_submit(NetworkMessage::FieldSelected(Field::Password).into())
+                            .size(Self::TEXT_SIZE)
+                            .padding(5),
+                    ]
+                    .spacing(10)
+                    .align_y(Center),
+                )
+                .push(
+                    row![field(Field::Password, tr!("network-password")), password]
+                        .spacing(10)
+                        .align_y(Center),
+                );
+        } else {
+            fields = fields.push(password);
+        }
+
+        column![
+            row![
+                text(title).size(Self::TEXT_SIZE),
+                horizontal_space(),
+                text(page.status.as_deref().unwrap_or_default()).size(15),
+                button(text(submit).size(Self::TEXT_SIZE))
+                    .on_press_maybe(
+                        (!page.connecting).then_some(NetworkMessage::EntrySubmit.into())
+                    )
+                    .padding(5),
+                button(text(tr!("cancel")).size(Self::TEXT_SIZE))
+                    .on_press(NetworkMessage::EntryCancel.into())
+                    .style(button::secondary)
+                    .padding(5),
+            ]
+            .spacing(10)
+            .align_y(Center),
+            fields,
+            widgets::keyboard(page.keyboard, |key| NetworkMessage::WifiKey(key).into()),
+        ]
+        .spacing(5)
+        .align_x(Center)
+        .into()
+    }
+}
+
+impl NetworkPage {
+    fn start_entry(&mut self, entry: Entry, field: Field) {
+        self.entry = Some(entry);
+        self.field = field;
+        self.ssid.clear();
+        self.password.clear();
+        self.keyboard = KeyboardMode::default();
+        self.status = None;
+    }
+}
+
+fn lock_icon<'a>(security: Security) -> Element<'a, Message> {
+    if security.is_secured() {
+        image("./res/icons/lock.png").width(24).height(24).into()
+    } else {
+        Space::with_width(24).into()
+    }
+}
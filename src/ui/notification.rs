@@ -0,0 +1,282 @@
+//! Error notifications
+//!
+//! Transient failures are shown as toasts over the bottom right corner and
+//! disappear after a while. Errors which need the user's attention are
+//! shown in the modal window until they are dismissed.
+
+use chrono::{DateTime, Local};
+use iced::{
+    Alignment::{Center, End},
+    Element, Length, Theme,
+    widget::{button, column, container, horizontal_space, row, text},
+};
+
+use super::{Ice, Message, Page};
+use crate::{
+    i18n::tr,
+    time::{DisplayMode, Time},
+};
+
+/// Toasts disappear after this time (in seconds)
+const TOAST_TIMEOUT: i64 = 15;
+
+/// Max number of the toasts on the screen
+const MAX_TOASTS: usize = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+
+    /// Transient failure which will be retried (toast)
+    Warning,
+
+    /// Failure which blocks the program (modal window)
+    Error,
+}
+
+/// Source of the notification. A new notification replaces the previous
+/// one with the same topic, and a successful operation clears it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Topic {
+    Config,
+    Location,
+    Weather,
+    Forecast,
+    Search,
+    Provisioning,
+}
+
+/// Button of the notification
+#[derive(Debug, Clone)]
+pub enum Action {
+    /// Sends the message of the failed operation again
+    Retry(Box<Message>),
+    Settings,
+    Network,
+}
+
+impl Action {
+    fn label(&self) -> String {
+        tr!(match self {
+            Self::Retry(_) => "action-retry",
+            Self::Settings => "action-settings",
+            Self::Network => "nav-network",
+        })
+    }
+
+    /// Returns the message which performs the action
+    pub fn message(self) -> Message {
+        match self {
+            Self::Retry(message) => *message,
+            Self::Settings => Message::ShowPage(Page::Settings),
+            Self::Network => Message::ShowPage(Page::Network),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub topic: Topic,
+    pub severity: Severity,
+    pub text: String,
+    pub time: DateTime<Local>,
+    pub actions: Vec<Action>,
+
+    /// Number of the same failures in a row
+    pub repeats: u32,
+}
+
+impl Notification {
+    pub fn new(topic: Topic, severity: Severity, text: impl Into<String>) -> Self {
+        Self {
+            topic,
+            severity,
+            text: text.into(),
+            time: Local::now(),
+            actions: vec![],
+            repeats: 1,
+        }
+    }
+
+    pub fn action(mut self, action: Action) -> Self {
+        self.actions.push(action);
+        self
+    }
+}
+
+/// Active notifications in order of their appearance
+#[derive(Debug, Default)]
+pub struct Notifications(Vec<Notification>);
+
+impl Notifications {
+    pub fn push(&mut self, mut notification: Notification) {
+        if let Some(pos) = self.0.iter().position(|n| n.topic == notification.topic) {
+            let old = self.0.remove(pos);
+            if old.text == notification.text {
+                notification.repeats += old.repeats;
+            }
+        }
+        self.0.push(notification);
+    }
+
+    /// Removes the notification of the `topic` (e.g. the failed operation
+    /// has succeeded)
+    pub fn clear(&mut self, topic: Topic) {
+        self.0.retain(|n| n.topic != topic);
+    }
+
+    /// Removes the toasts older than [`TOAST_TIMEOUT`]
+    pub fn expire(&mut self, now: DateTime<Local>) {
+        self.0.retain(|n| {
+            n.severity == Severity::Error || (now - n.time).num_seconds() < TOAST_TIMEOUT
+        });
+    }
+
+    /// Returns the oldest blocking error
+    pub fn error(&self) -> Option<&Notification> {
+        self.0.iter().find(|n| n.severity == Severity::Error)
+    }
+
+    fn toasts(&self) -> impl Iterator<Item = &Notification> {
+        let toasts = self.0.iter().filter(|n| n.severity != Severity::Error);
+        toasts.rev().take(MAX_TOASTS)
+    }
+}
+
+impl Ice {
+    /// Shows the `notification`
+    pub(super) fn notify(&mut self, notification: Notification) {
+        self.notifications.push(notification);
+    }
+
+    /// Shows the error of the `topic` or clears the previous one if the
+    /// operation has succeeded
+    pub(super) fn report(
+        &mut self,
+        topic: Topic,
+        error: Option<String>,
+        make: impl FnOnce(String) -> Notification,
+    ) {
+        match error {
+            Some(error) => self.notify(make(error)),
+            None => self.notifications.clear(topic),
+        }
+    }
+
+    /// Toasts placed in the bottom right corner above the bottom bar
+    pub(super) fn toasts(&self) -> Element<'_, Message> {
+        container(
+            column(self.notifications.toasts().map(|n| toast(n, &self.ctime)))
+                .spacing(5)
+                .width(350),
+        )
+        .align_right(Length::Fill)
+        .align_bottom(Length::Fill)
+        .padding([50, 10])
+        .into()
+    }
+}
+
+/// Time of the notification with the number of the repeats
+pub(super) fn timestamp(n: &Notification) -> String {
+    let time = Time::new(&n.time).set_display_mode(DisplayMode::TimeWithSeconds);
+    if n.repeats > 1 {
+        format!("{time} (×{})", n.repeats)
+    } else {
+        time.to_string()
+    }
+}
+
+/// Buttons of the notification actions. The notification is dismissed when
+/// any of them is pressed
+pub(super) fn actions<'a>(n: &Notification, size: u16) -> Element<'a, Message> {
+    row(n.actions.iter().map(|action| {
+        button(text(action.label()).size(size))
+            .on_press(Message::NotificationAction(n.topic, action.clone()))
+            .padding([3, 8])
+            .into()
+    }))
+    .spacing(5)
+    .into()
+}
+
+fn toast<'a>(n: &'a Notification, now: &DateTime<Local>) -> Element<'a, Message> {
+    let severity = n.severity;
+    // Dimmed in the last seconds before disappearing
+    let age = (*now - n.time).num_seconds();
+
+    container(
+        column![
+            row![
+                text(timestamp(n)).size(12),
+                horizontal_space(),
+                button(text("×").size(15))
+                    .on_press(Message::DismissNotification(n.topic))
+                    .style(button::text)
+                    .padding(0),
+            ]
+            .align_y(Center),
+            text(&n.text).size(15),
+            actions(n, 12),
+        ]
+        .spacing(3)
+        .align_x(End),
+    )
+    .width(Length::Fill)
+    .padding(8)
+    .style(move |theme: &Theme| {
+        let palette = theme.extended_palette();
+        let accent = match severity {
+            Severity::Info => palette.primary.base.color,
+            Severity::Warning | Severity::Error => palette.danger.base.color,
+        };
+        let mut style = container::rounded_box(theme);
+        style.border = style.border.color(accent).width(2);
+        if age >= TOAST_TIMEOUT - 3 {
+            style = style.background(palette.background.weak.color);
+        }
+        style
+    })
+    .into()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn notifications_test() {
+        let mut list = Notifications::default();
+        list.push(Notification::new(
+            Topic::Weather,
+            Severity::Warning,
+            "timeout",
+        ));
+        list.push(Notification::new(
+            Topic::Weather,
+            Severity::Warning,
+            "timeout",
+        ));
+        list.push(Notification::new(
+            Topic::Config,
+            Severity::Error,
+            "parse error",
+        ));
+
+        assert_eq!(list.0.len(), 2);
+        assert_eq!(list.0[0].repeats, 2);
+        assert_eq!(list.error().map(|n| n.topic), Some(Topic::Config));
+        assert_eq!(list.toasts().count(), 1);
+
+        // Another error of the same topic resets the counter
+        list.push(Notification::new(Topic::Weather, Severity::Warning, "404"));
+        assert_eq!(list.0.last().map(|n| n.repeats), Some(1));
+
+        list.expire(Local::now() + chrono::Duration::seconds(TOAST_TIMEOUT));
+        assert_eq!(list.toasts().count(), 0);
+        assert!(list.error().is_some());
+
+        list.clear(Topic::Config);
+        assert!(list.error().is_none());
+    }
+}
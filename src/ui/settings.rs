@@ -0,0 +1,360 @@
+//! Settings page
+
+use iced::{
+    Alignment::Center,
+    Element, Length, Task,
+    widget::{button, column, container, horizontal_space, row, scrollable, text, text_input},
+};
+
+use super::{
+    Ice, Message,
+    widgets::{self, Key, KeyboardMode},
+};
+use crate::{
+    config::{Config, Language, ThemeMode},
+    consts::CONF_PATH,
+    i18n::{self, tr},
+    time::TimeFormat,
+    units::{Units, Variant},
+};
+
+/// Weather update intervals (in minutes) offered on the page
+const REFRESH_INTERVALS: [u64; 5] = [10, 15, 30, 60, 120];
+
+/// State of the settings page. Changes are made in the copy of the config
+/// and applied by the "Save" button only
+#[derive(Debug, Default)]
+pub struct SettingsPage {
+    draft: Config,
+
+    /// The API key is being entered with the on-screen keyboard
+    appid_entry: bool,
+    keyboard: KeyboardMode,
+
+    /// Result of the last saving
+    status: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum SettingsMessage {
+    Units(Units),
+    /// Detect the location automatically or use the selected city
+    Autodetect(bool),
+    /// Move the IP geolocation provider to the top of the list
+    PrimaryProvider(usize),
+    RefreshInterval(u64),
+    Theme(ThemeMode),
+    TimeFormat(TimeFormat),
+    Language(Language),
+
+    EditAppid,
+    AppidChanged(String),
+    AppidKey(Key),
+    AppidDone,
+
+    Save,
+    /// Discard unsaved changes
+    Reset,
+}
+
+impl From<SettingsMessage> for Message {
+    fn from(message: SettingsMessage) -> Self {
+        Self::Settings(message)
+    }
+}
+
+impl SettingsPage {
+    /// Starts editing of the `conf`
+    pub fn open(&mut self, conf: &Config) {
+        self.draft = conf.clone();
+        self.appid_entry = false;
+        self.status = None;
+    }
+
+    /// Sets the location selected in the modal window
+    pub fn set_coords(&mut self, coords: (f32, f32)) {
+        self.draft.coords = Some(coords);
+        self.draft.autodetect_location = false;
+    }
+}
+
+impl Ice {
+    pub(super) fn update_settings(&mut self, message: SettingsMessage) -> Task<Message> {
+        let page = &mut self.settings;
+        let draft = &mut page.draft;
+
+        match message {
+            SettingsMessage::Units(units) => draft.units = units,
+            SettingsMessage::Autodetect(autodetect) => {
+                draft.autodetect_location = autodetect;
+                // Manual coordinates are tried before the autodetection
+                draft.coords = if autodetect {
+                    None
+                } else {
+                    draft.coords.or(self.conf.coords)
+                };
+            }
+            SettingsMessage::PrimaryProvider(index) => {
+                if index < draft.geoip.len() {
+                    let provider = draft.geoip.remove(index);
+                    draft.geoip.insert(0, provider);
+                }
+            }
+            SettingsMessage::RefreshInterval(minutes) => draft.refresh_interval = minutes,
+            SettingsMessage::Theme(theme) => draft.interface.theme = theme,
+            SettingsMessage::TimeFormat(format) => draft.interface.time_format = format,
+            SettingsMessage::Language(language) => draft.interface.language = language,
+
+            SettingsMessage::EditAppid => {
+                page.appid_entry = true;
+                page.keyboard = KeyboardMode::default();
+            }
+            SettingsMessage::AppidChanged(appid) => draft.appid = appid,
+            SettingsMessage::AppidKey(key) => {
+                if key.apply(&mut draft.appid, &mut page.keyboard) {
+                    page.appid_entry = false;
+                }
+            }
+            SettingsMessage::AppidDone => {
+                draft.appid = draft.appid.trim().to_string();
+                page.appid_entry = false;
+            }
+
+            SettingsMessage::Save => {
+                draft.appid = draft.appid.trim().to_string();
+                if let Err(why) = draft.validate().and_then(|_| draft.write(CONF_PATH)) {
+                    page.status = Some(why.to_string());
+                    return Task::none();
+                }
+                i18n::set_language(draft.interface.language);
+                page.status = Some(tr!("settings-saved"));
+
+                let conf = draft.clone();
+                let relocate = (conf.coords, conf.autodetect_location, &conf.geoip)
+                    != (
+                        self.conf.coords,
+                        self.conf.autodetect_location,
+                        &self.conf.geoip,
+                    );
+                let reload =
+                    relocate || conf.units != self.conf.units || conf.appid != self.conf.appid;
+
+                if relocate {
+                    self.forget_location();
+                }
+                if reload {
+                    self.invalidate_weather();
+                    self.current_weather = None; // weather will be reloaded
+                    self.daily_weather = None;
+                }
+                self.conf = conf;
+            }
+            SettingsMessage::Reset => page.open(&self.conf),
+        }
+        Task::none()
+    }
+
+    pub(super) fn settings_page(&self) -> Element<'_, Message> {
+        let page = &self.settings;
+        let draft = &page.draft;
+
+        if page.appid_entry {
+            return container(
+                column![
+                    row![
+                        text(tr!("settings-appid-title")).size(Self::TEXT_SIZE),
+                        horizontal_space(),
+                        button(text(tr!("settings-done")).size(Self::TEXT_SIZE))
+                            .on_press(SettingsMessage::AppidDone.into())
+                            .padding(5),
+                    ]
+                    .align_y(Center),
+                    text_input(&tr!("settings-appid"), &draft.appid)
+                        .on_input(|appid| SettingsMessage::AppidChanged(appid).into())
+                        .on_submit(SettingsMessage::AppidDone.into())
+                        .size(Self::TEXT_SIZE)
+                        .padding(5),
+                    widgets::keyboard(page.keyboard, |key| SettingsMessage::AppidKey(key).into()),
+                ]
+                .spacing(5)
+                .align_x(Center),
+            )
+            .padding([0, 10])
+            .into();
+        }
+
+        let location = row![choice(
+            &[
+                (true, tr!("settings-location-auto")),
+                (false, tr!("settings-location-manual")),
+            ],
+            draft.autodetect_location,
+            SettingsMessage::Autodetect,
+        )]
+        .push_maybe((!draft.autodetect_location).then(|| {
+            let selected = match (draft.coords, &self.selected_location) {
+                (Some(coords), Some(city)) if coords == (city.lat, city.lon) => city.to_string(),
+                (Some((lat, lon)), _) => format!("{lat:.2}, {lon:.2}"),
+                (None, _) => tr!("settings-location-none"),
+            };
+            row![
+                text(selected).size(15),
+                button(text(tr!("settings-location-select")).size(15))
+                    .on_press(Message::ShowModal(super::modal::Modal::Location)),
+            ]
+            .spacing(10)
+            .align_y(Center)
+        }))
+        .spacing(10)
+        .align_y(Center);
+
+        let providers = row(draft.geoip.iter().enumerate().map(|(i, provider)| {
+            button(text(provider.name()).size(15))
+                .on_press(SettingsMessage::PrimaryProvider(i).into())
+                .style(if i == 0 {
+                    button::primary
+                } else {
+                    button::secondary
+                })
+                .into()
+        }))
+        .spacing(5);
+
+        let intervals = REFRESH_INTERVALS.map(|minutes| {
+            (
+                minutes,
+                if minutes < 60 {
+                    tr!("settings-minutes", minutes = minutes)
+                } else {
+                    tr!("settings-hours", hours = minutes / 60)
+                },
+            )
+        });
+
+        let settings = column![
+            setting(
+                tr!("settings-units"),
+                choice(
+                    &[
+                        (
+                            Units::Metric,
+                            format!("°C, {}", Variant::Speed.to_str(Units::Metric)),
+                        ),
+                        (
+                            Units::Imperial,
+                            format!("°F, {}", Variant::Speed.to_str(Units::Imperial)),
+                        ),
+                    ],
+                    draft.units,
+                    SettingsMessage::Units,
+                ),
+            ),
+            setting(tr!("nav-location"), location.into()),
+            setting(
+                tr!("settings-appid"),
+                row![
+                    text(&draft.appid).size(15),
+                    button(text(tr!("settings-edit")).size(15))
+                        .on_press(SettingsMessage::EditAppid.into()),
+                ]
+                .spacing(10)
+                .align_y(Center)
+                .into(),
+            ),
+            setting(tr!("settings-geoip"), providers.into()),
+            setting(
+                tr!("settings-refresh-interval"),
+                choice(
+                    &intervals,
+                    draft.refresh_interval,
+                    SettingsMessage::RefreshInterval,
+                ),
+            ),
+            setting(
+                tr!("settings-theme"),
+                choice(
+                    &[
+                        (ThemeMode::Auto, tr!("settings-auto")),
+                        (ThemeMode::Light, tr!("settings-theme-light")),
+                        (ThemeMode::Dark, tr!("settings-theme-dark")),
+                    ],
+                    draft.interface.theme,
+                    SettingsMessage::Theme,
+                ),
+            ),
+            setting(
+                tr!("settings-time-format"),
+                choice(
+                    &[
+                        (TimeFormat::H24, tr!("settings-time-24h")),
+                        (TimeFormat::H12, tr!("settings-time-12h")),
+                    ],
+                    draft.interface.time_format,
+                    SettingsMessage::TimeFormat,
+                ),
+            ),
+            setting(
+                tr!("settings-language"),
+                choice(
+                    // Names of the languages aren't translated
+                    &[
+                        (Language::Auto, tr!("settings-auto")),
+                        (Language::Ru, "Русский".to_string()),
+                        (Language::En, "English".to_string()),
+                    ],
+                    draft.interface.language,
+                    SettingsMessage::Language,
+                ),
+            ),
+        ]
+        .spacing(10);
+
+        let changed = *draft != self.conf;
+        let header = row![
+            text(page.status.as_deref().unwrap_or_default()).size(15),
+            horizontal_space(),
+            button(text(tr!("settings-reset")).size(Self::TEXT_SIZE))
+                .on_press_maybe(changed.then_some(SettingsMessage::Reset.into()))
+                .style(button::secondary)
+                .padding(5),
+            button(text(tr!("settings-save")).size(Self::TEXT_SIZE))
+                .on_press_maybe(changed.then_some(SettingsMessage::Save.into()))
+                .padding(5),
+        ]
+        .spacing(10)
+        .align_y(Center);
+
+        container(column![header, scrollable(settings).height(Length::Fill)].spacing(10))
+            .padding([0, 10])
+            .into()
+    }
+}
+
+/// Row with the name and the control of the setting
+fn setting<'a>(name: String, control: Element<'a, Message>) -> Element<'a, Message> {
+    row![text(name).size(Ice::TEXT_SIZE).width(220), control]
+        .spacing(10)
+        .align_y(Center)
+        .into()
+}
+
+/// Group of buttons with the `current` option highlighted
+fn choice<'a, T: Copy + PartialEq>(
+    options: &[(T, String)],
+    current: T,
+    on_select: impl Fn(T) -> SettingsMessage,
+) -> Element<'a, Message> {
+    row(options.iter().map(|(value, label)| {
+        button(text(label.clone()).size(15))
+            .on_press(on_select(*value).into())
+            .style(if *value == current {
+                button::primary
+            } else {
+                button::secondary
+            })
+            .padding([8, 12])
+            .into()
+    }))
+    .spacing(5)
+    .into()
+}
@@ -0,0 +1,292 @@
+//! System monitor page
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    path::PathBuf,
+    time::Duration,
+};
+
+use iced::{
+    Alignment::Center,
+    Element, Length, Task,
+    widget::{column, container, row, scrollable, text},
+};
+
+use super::{
+    Ice, Message,
+    widgets::{self, Level},
+};
+use crate::{
+    app::system::{CpuStat, Snapshot, System},
+    i18n::tr,
+};
+
+/// Interval between the readings
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Number of the readings shown on the charts (the last hour)
+const HISTORY_LEN: usize = 360;
+
+/// Thresholds (warning, danger) of the CPU load (in percent)
+const CPU_LEVELS: (f32, f32) = (80., 95.);
+
+/// Thresholds (warning, danger) of the RAM usage (in percent)
+const MEMORY_LEVELS: (f32, f32) = (80., 90.);
+
+/// Thresholds (warning, danger) of the disk usage (in percent)
+const DISK_LEVELS: (f32, f32) = (90., 95.);
+
+/// Thresholds (warning, danger) of the SoC temperature (in °C). Most
+/// single-board computers start throttling at 80-85 °C
+const TEMP_LEVELS: (f32, f32) = (70., 80.);
+
+/// Max temperature on the chart
+const TEMP_MAX: f32 = 100.;
+
+/// Rolling history of the readings
+#[derive(Debug, Default)]
+struct History(VecDeque<f32>);
+
+impl History {
+    fn push(&mut self, value: f32) {
+        if self.0.len() == HISTORY_LEN {
+            self.0.pop_front();
+        }
+        self.0.push_back(value);
+    }
+
+    fn last(&self) -> Option<f32> {
+        self.0.back().copied()
+    }
+
+    fn values(&self) -> impl Iterator<Item = f32> + '_ {
+        self.0.iter().copied()
+    }
+}
+
+/// State of the system monitor page. Readings are collected in the
+/// background, so the charts are filled when the page is opened
+#[derive(Debug, Default)]
+pub struct SystemPage {
+    snapshot: Option<Snapshot>,
+
+    /// CPU times of the previous reading to calculate the load
+    prev_cpu: Option<CpuStat>,
+    cpu: History,
+    memory: History,
+    temperature: History,
+    disks: BTreeMap<PathBuf, History>,
+
+    error: Option<String>,
+}
+
+impl Ice {
+    /// Reads the system state in the background
+    pub(super) fn sample_system(&self) -> Task<Message> {
+        let mounts = self.conf.system.mounts.clone();
+        Task::perform(
+            async move {
+                tokio::task::spawn_blocking(move || System::default().snapshot(&mounts))
+                    .await
+                    .map_err(anyhow::Error::from)
+                    .and_then(|snapshot| snapshot)
+            },
+            |snapshot| {
+                Message::SystemSampled(match snapshot {
+                    Ok(snapshot) => (Some(snapshot), None),
+                    Err(why) => (None, Some(why.to_string())),
+                })
+            },
+        )
+    }
+
+    pub(super) fn system_sampled(
+        &mut self,
+        (snapshot, error): (Option<Snapshot>, Option<String>),
+    ) -> Task<Message> {
+        let page = &mut self.system;
+        page.error = error;
+        let Some(snapshot) = snapshot else {
+            return Task::none();
+        };
+
+        if let Some(prev) = &page.prev_cpu {
+            page.cpu.push(snapshot.cpu.total.load_since(&prev.total));
+        }
+        page.memory.push(snapshot.memory.usage());
+        if let Some(thermal) = &snapshot.temperature {
+            page.temperature.push(thermal.temp);
+        }
+        for disk in &snapshot.disks {
+            page.disks
+                .entry(disk.mount.clone())
+                .or_default()
+                .push(disk.usage());
+        }
+
+        page.prev_cpu = Some(snapshot.cpu.clone());
+        page.snapshot = Some(snapshot);
+        Task::none()
+    }
+
+    pub(super) fn system_page(&self) -> Element<'_, Message> {
+        let page = &self.system;
+        let Some(snapshot) = &page.snapshot else {
+            return container(
+                text(match &page.error {
+                    Some(error) => error.clone(),
+                    None => tr!("system-loading"),
+                })
+                .size(Self::TEXT_SIZE),
+            )
+            .center(Length::Fill)
+            .into();
+        };
+
+        let mut metrics = column![].spacing(10);
+
+        let cpu = page.cpu.last();
+        metrics = metrics.push(metric(
+            "CPU".to_string(),
+            match cpu {
+                Some(load) => format!("{load:.0}%"),
+                None => "—".to_string(),
+            },
+            tr!("system-cores", cores = snapshot.cpu.cores.len()),
+            &page.cpu,
+            100.,
+            cpu.map_or(Level::Normal, |load| {
+                Level::of(load, CPU_LEVELS.0, CPU_LEVELS.1)
+            }),
+        ));
+
+        let memory = &snapshot.memory;
+        metrics = metrics.push(metric(
+            tr!("system-memory"),
+            format!("{:.0}%", memory.usage()),
+            tr!(
+                "system-memory-details",
+                used = bytes(memory.used()),
+                total = bytes(memory.total),
+                swap = bytes(memory.swap_used()),
+            ),
+            &page.memory,
+            100.,
+            Level::of(memory.usage(), MEMORY_LEVELS.0, MEMORY_LEVELS.1),
+        ));
+
+        if let Some(thermal) = &snapshot.temperature {
+            let throttled = snapshot.throttling.is_throttled();
+            let level = if throttled {
+                Level::Danger
+            } else {
+                Level::of(thermal.temp, TEMP_LEVELS.0, TEMP_LEVELS.1)
+            };
+            metrics = metrics.push(metric(
+                tr!("system-temperature"),
+                format!("{:.1} °C", thermal.temp),
+                if throttled {
+                    tr!("system-throttled")
+                } else {
+                    thermal.kind.clone()
+                },
+                &page.temperature,
+                TEMP_MAX,
+                level,
+            ));
+        }
+
+        for disk in &snapshot.disks {
+            let Some(history) = page.disks.get(&disk.mount) else {
+                continue;
+            };
+            metrics = metrics.push(metric(
+                tr!("system-disk"),
+                format!("{:.0}%", disk.usage()),
+                tr!(
+                    "system-disk-details",
+                    mount = disk.mount.display().to_string(),
+                    available = bytes(disk.available),
+                    total = bytes(disk.total),
+                ),
+                history,
+                100.,
+                Level::of(disk.usage(), DISK_LEVELS.0, DISK_LEVELS.1),
+            ));
+        }
+
+        let throttling = &snapshot.throttling;
+        let mut status = vec![tr!(
+            "system-uptime",
+            time = crate::time::duration(snapshot.uptime.as_secs())
+        )];
+        if let Some(freq) = throttling.cur_freq {
+            status.push(tr!("system-cpu-freq", freq = freq / 1000));
+        }
+        if throttling.under_voltage() {
+            status.push(tr!("system-under-voltage"));
+        } else if throttling.under_voltage_occurred() {
+            status.push(tr!("system-under-voltage-occurred"));
+        }
+
+        let mut content = column![
+            metrics,
+            text(status.join(" · ")).size(12).style(move |theme| {
+                text::Style {
+                    color: throttling
+                        .under_voltage()
+                        .then(|| Level::Danger.color(theme)),
+                }
+            }),
+        ]
+        .spacing(10);
+        if let Some(error) = &page.error {
+            content = content.push(text(error).size(12));
+        }
+
+        scrollable(content.padding([0, 10])).into()
+    }
+}
+
+/// Row with the name, the current value and the chart of the metric
+fn metric<'a>(
+    name: String,
+    value: String,
+    details: String,
+    history: &History,
+    max: f32,
+    level: Level,
+) -> Element<'a, Message> {
+    row![
+        column![
+            text(name).size(Ice::TEXT_SIZE),
+            text(value)
+                .size(Ice::TEXT_SIZE)
+                .style(move |theme| text::Style {
+                    color: (level != Level::Normal).then(|| level.color(theme)),
+                }),
+        ]
+        .width(130),
+        column![
+            widgets::sparkline(history.values(), HISTORY_LEN, max, level),
+            text(details).size(12),
+        ]
+        .spacing(3),
+    ]
+    .spacing(10)
+    .align_y(Center)
+    .into()
+}
+
+/// Formats the size in bytes (`1.5 GiB`)
+fn bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["unit-kib", "unit-mib", "unit-gib", "unit-tib"];
+
+    let mut size = bytes as f64 / 1024.;
+    let mut unit = 0;
+    while size >= 1024. && unit < UNITS.len() - 1 {
+        size /= 1024.;
+        unit += 1;
+    }
+    tr!(UNITS[unit], size = format!("{size:.1}"))
+}
@@ -0,0 +1,162 @@
+//! Data modifying
+
+use chrono::{DateTime, Local};
+use iced::Task;
+
+use super::{Ice, Message, Page, network::NetworkMessage};
+use crate::app::{
+    location,
+    network::{NM_STATE_CONNECTED_GLOBAL, NetworkChange},
+};
+
+impl Ice {
+    /// Starts the location detection. `skip_cache` is used after the
+    /// network change, when the cached location may be outdated
+    pub(super) fn resolve_location(&mut self, skip_cache: bool) -> Task<Message> {
+        // GPS may wait for the fix for a long time. The cached location
+        // may be returned by the running detection, so the redetection is
+        // postponed, not dropped
+        if self.location_pending {
+            self.location_redetect |= skip_cache;
+            return Task::none();
+        }
+        self.location_pending = true;
+        self.location_requested = Some(self.ctime);
+
+        let resolver = location::Resolver::new(&self.conf).skip_cache(skip_cache);
+        Task::perform(
+            async move {
+                match resolver.resolve().await {
+                    Ok(loc) => (Some(loc), None),
+                    Err(why) => (None, Some(why.to_string())),
+                }
+            },
+            Message::LocationResolved,
+        )
+    }
+
+    /// Checks whether the failed location detection should be repeated.
+    /// The delay is doubled after each failure, so the unavailable GPS or
+    /// GeoIP services aren't polled every second
+    pub(super) fn location_due(&self) -> bool {
+        let Some(requested) = self.location_requested else {
+            return true;
+        };
+        let delay = Self::LOCATION_RETRY
+            .saturating_mul(1 << self.location_failures.min(16))
+            .min(Self::LOCATION_RETRY_MAX);
+        (self.ctime - requested).num_seconds() >= delay
+    }
+
+    /// Checks whether the autodetected location should be checked again
+    pub(super) fn location_outdated(&self) -> bool {
+        let Some(loc) = &self.location else {
+            return false;
+        };
+        let requested = self
+            .location_requested
+            .is_none_or(|time| (self.ctime - time).num_seconds() >= Self::LOCATION_REFRESH);
+        loc.source != location::Source::Manual && loc.age() >= Self::LOCATION_REFRESH && requested
+    }
+
+    /// Forgets the location, so it's detected again (e.g. with the new
+    /// settings) without the retry delay
+    pub(super) fn forget_location(&mut self) {
+        self.location = None;
+        self.location_requested = None;
+        self.location_failures = 0;
+        self.invalidate_weather();
+    }
+
+    /// Drops the running weather requests: their responses are for the
+    /// previous location or settings. New requests may be sent at once
+    pub(super) fn invalidate_weather(&mut self) {
+        self.weather_generation = self.weather_generation.wrapping_add(1);
+        self.weather_pending = false;
+        self.daily_pending = false;
+    }
+
+    /// Forgets the weather requests which haven't been answered in time,
+    /// so a lost response doesn't block the updates forever
+    pub(super) fn expire_weather_requests(&mut self) {
+        let expired = |time: Option<DateTime<Local>>| {
+            time.is_none_or(|time| (self.ctime - time).num_seconds() >= Self::WEATHER_TIMEOUT)
+        };
+        if self.weather_pending && expired(self.weather_requested) {
+            self.weather_pending = false;
+        }
+        if self.daily_pending && expired(self.daily_requested) {
+            self.daily_pending = false;
+        }
+    }
+
+    /// Interval between the weather updates (in seconds)
+    pub(super) fn refresh_interval(&self) -> i64 {
+        self.conf.refresh_interval as i64 * 60
+    }
+
+    /// Checks whether the weather should be requested: it's outdated and
+    /// the previous request isn't too recent
+    pub(super) fn weather_due(&self) -> bool {
+        if self.weather_pending || self.location.is_none() {
+            return false;
+        }
+        let since = |time: Option<DateTime<Local>>| {
+            time.map_or(i64::MAX, |time| (self.ctime - time).num_seconds())
+        };
+
+        let retry = if self.current_weather.is_some() {
+            Self::WEATHER_RETRY
+        } else {
+            Self::WEATHER_RETRY_NO_DATA
+        };
+        let outdated = self.current_weather.is_none()
+            || since(self.weather_updated) >= self.refresh_interval();
+        outdated && since(self.weather_requested) >= retry
+    }
+
+    /// Detects the location again when the device gets connected to the
+    /// Internet or the active connection changes. Also refreshes the
+    /// connection details on the network page
+    pub(super) fn network_changed(&mut self, change: NetworkChange) -> Task<Message> {
+        let redetect = match change {
+            NetworkChange::State(state) => {
+                // The first state is the current one, not a change
+                let was_connected = self
+                    .network_state
+                    .is_none_or(|state| state == NM_STATE_CONNECTED_GLOBAL);
+                self.network_state = Some(state);
+                state == NM_STATE_CONNECTED_GLOBAL && !was_connected
+            }
+            NetworkChange::PrimaryConnection(path) => {
+                let changed = self
+                    .primary_connection
+                    .as_ref()
+                    .is_some_and(|old| old != &path);
+                self.primary_connection = Some(path.clone());
+                changed && path != "/"
+            }
+            NetworkChange::Connectivity(connectivity) => {
+                self.connectivity = Some(connectivity);
+                false
+            }
+            NetworkChange::WifiSignal(signal) => {
+                self.wifi_signal = signal;
+                false
+            }
+        };
+
+        let location = if redetect && self.location.is_some() {
+            Task::done(Message::RedetectLocation)
+        } else {
+            Task::none()
+        };
+        let page = if self.page == Page::Network {
+            Task::done(NetworkMessage::GetConnectionInfo.into())
+        } else {
+            Task::none()
+        };
+
+        Task::batch([location, page])
+    }
+}
@@ -0,0 +1,56 @@
+//! Weather page
+
+use iced::{
+    Alignment::Center,
+    Element,
+    widget::{button, center, column, container, image, row, scrollable, text},
+};
+
+use super::{Ice, Message, Page};
+use crate::{api::floor, i18n::tr, units::Variant};
+
+impl Ice {
+    pub(super) fn weather_page(&self) -> Element<'_, Message> {
+        let image = container(center(
+            column![
+                text(match &self.current_weather {
+                    Some(current) => current.weather[0].get_descr(),
+                    None => tr!("loading"),
+                })
+                .size(Self::TEXT_SIZE),
+                // Details of the weather are shown by tapping the icon
+                button(image(format!(
+                    "./res/icons/{}.png",
+                    match &self.current_weather {
+                        Some(current) => current.weather[0].get_icon(self.ctime),
+                        None => "default",
+                    }
+                )))
+                .on_press(Message::ShowPage(Page::Current))
+                .style(button::text)
+                .padding(0),
+                text(format!(
+                    "{}{}",
+                    floor(match &self.current_weather {
+                        Some(current) => current.main.feels_like,
+                        None => 0.,
+                    }),
+                    Variant::Degrees.to_str(self.conf.units),
+                ))
+                .size(35)
+            ]
+            .spacing(10)
+            .align_x(Center),
+        ))
+        .padding(10)
+        .width(260);
+
+        row![
+            image,
+            center(scrollable(text(tr!("weather-hourly-placeholder")).size(25),))
+        ]
+        .spacing(Self::TEXT_SIZE)
+        .align_y(Center)
+        .into()
+    }
+}
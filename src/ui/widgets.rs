@@ -0,0 +1,523 @@
+//! Custom widgets
+
+use iced::{
+    Alignment::{Center, End},
+    Color, Element, Length, Point, Rectangle, Renderer, Theme, Vector, mouse,
+    widget::{button, canvas, column, container, row, text},
+};
+
+use crate::{
+    api::{Wind, beaufort},
+    app::network::Connectivity,
+    consts::DEFAULT_WIN_SIZE,
+    i18n::tr,
+    units::Units,
+};
+
+/// Wi-Fi signal strength as four bars of increasing height
+pub fn signal_bars<'a, M: 'a>(strength: u8) -> Element<'a, M> {
+    // 0-100% -> 0-4 bars
+    let active = match strength {
+        0..=20 => 1,
+        21..=45 => 2,
+        46..=70 => 3,
+        _ => 4,
+    };
+
+    row((1..=4).map(|bar| {
+        container("")
+            .width(5)
+            .height(bar * 5)
+            .style(move |theme: &Theme| {
+                let palette = theme.extended_palette();
+                container::Style {
+                    background: Some(
+                        if bar <= active {
+                            palette.primary.strong.color
+                        } else {
+                            palette.background.strong.color
+                        }
+                        .into(),
+                    ),
+                    ..container::Style::default()
+                }
+            })
+            .into()
+    }))
+    .spacing(2)
+    .align_y(End)
+    .into()
+}
+
+/// Colored dot with the short description of the Internet connectivity
+pub fn connectivity<'a, M: 'a>(connectivity: Option<Connectivity>) -> Element<'a, M> {
+    let label = tr!(match connectivity {
+        Some(Connectivity::Full) => "connectivity-full",
+        Some(Connectivity::Limited) => "connectivity-limited",
+        Some(Connectivity::Portal) => "connectivity-portal",
+        Some(Connectivity::None) => "connectivity-none",
+        Some(Connectivity::Unknown) | None => "connectivity-unknown",
+    });
+
+    let dot = container("")
+        .width(12)
+        .height(12)
+        .style(move |theme: &Theme| {
+            let palette = theme.extended_palette();
+            let color = match connectivity {
+                Some(Connectivity::Full) => palette.success.base.color,
+                Some(Connectivity::Limited | Connectivity::Portal) => palette.danger.weak.color,
+                Some(Connectivity::None) => palette.danger.base.color,
+                Some(Connectivity::Unknown) | None => palette.background.strong.color,
+            };
+            container::Style {
+                background: Some(color.into()),
+                border: iced::border::rounded(6),
+                ..container::Style::default()
+            }
+        });
+
+    row![dot, text(label).size(12)]
+        .spacing(5)
+        .align_y(Center)
+        .into()
+}
+
+/// Severity of the measured value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub enum Level {
+    #[default]
+    Normal,
+    Warning,
+    Danger,
+}
+
+impl Level {
+    /// Compares `value` with the `warning` and `danger` thresholds
+    pub fn of(value: f32, warning: f32, danger: f32) -> Self {
+        if value >= danger {
+            Self::Danger
+        } else if value >= warning {
+            Self::Warning
+        } else {
+            Self::Normal
+        }
+    }
+
+    pub fn color(self, theme: &Theme) -> Color {
+        let palette = theme.extended_palette();
+        match self {
+            Self::Normal => palette.primary.base.color,
+            Self::Warning => palette.danger.weak.color,
+            Self::Danger => palette.danger.base.color,
+        }
+    }
+}
+
+/// Small line chart of the recent values
+struct Sparkline {
+    values: Vec<f32>,
+
+    /// Number of values shown on the full width. The chart is filled from
+    /// the right while there are fewer values
+    capacity: usize,
+    max: f32,
+    level: Level,
+}
+
+impl<M> canvas::Program<M> for Sparkline {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry> {
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+        let color = self.level.color(theme);
+
+        frame.fill_rectangle(
+            Point::ORIGIN,
+            bounds.size(),
+            theme.extended_palette().background.weak.color,
+        );
+
+        let step = bounds.width / self.capacity.saturating_sub(1).max(1) as f32;
+        let offset = self.capacity.saturating_sub(self.values.len()) as f32 * step;
+        let points = self
+            .values
+            .iter()
+            .enumerate()
+            .map(|(i, value)| {
+                let value = (value / self.max).clamp(0., 1.);
+                Point::new(
+                    offset + i as f32 * step,
+                    bounds.height - value * (bounds.height - 2.) - 1.,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        if let [first, .., last] = points.as_slice() {
+            let line = canvas::Path::new(|path| {
+                path.move_to(*first);
+                for point in &points[1..] {
+                    path.line_to(*point);
+                }
+            });
+            let area = canvas::Path::new(|path| {
+                path.move_to(Point::new(first.x, bounds.height));
+                for point in &points {
+                    path.line_to(*point);
+                }
+                path.line_to(Point::new(last.x, bounds.height));
+                path.close();
+            });
+
+            frame.fill(&area, Color { a: 0.3, ..color });
+            frame.stroke(
+                &line,
+                canvas::Stroke::default().with_color(color).with_width(1.5),
+            );
+        }
+
+        vec![frame.into_geometry()]
+    }
+}
+
+/// Line chart of `values` from 0 to `max` colored according to the `level`
+pub fn sparkline<'a, M: 'a>(
+    values: impl IntoIterator<Item = f32>,
+    capacity: usize,
+    max: f32,
+    level: Level,
+) -> Element<'a, M> {
+    canvas(Sparkline {
+        values: values.into_iter().collect(),
+        capacity,
+        max,
+        level,
+    })
+    .width(Length::Fill)
+    .height(40)
+    .into()
+}
+
+/// Colors of the Beaufort forces 0-12: from calm blue through green and
+/// yellow to the storm red and purple
+const BEAUFORT_COLORS: [Color; 13] = [
+    Color::from_rgb(0.60, 0.80, 0.95),
+    Color::from_rgb(0.45, 0.75, 0.95),
+    Color::from_rgb(0.35, 0.80, 0.75),
+    Color::from_rgb(0.40, 0.80, 0.45),
+    Color::from_rgb(0.60, 0.85, 0.30),
+    Color::from_rgb(0.85, 0.85, 0.25),
+    Color::from_rgb(0.95, 0.75, 0.20),
+    Color::from_rgb(0.95, 0.55, 0.15),
+    Color::from_rgb(0.95, 0.35, 0.15),
+    Color::from_rgb(0.90, 0.20, 0.20),
+    Color::from_rgb(0.80, 0.15, 0.35),
+    Color::from_rgb(0.65, 0.15, 0.55),
+    Color::from_rgb(0.50, 0.15, 0.65),
+];
+
+/// Compass rose with the wind needle
+struct Compass {
+    /// Direction the wind blows from (in degrees)
+    deg: u16,
+    color: Color,
+
+    /// Color of the gusts ring
+    gust: Option<Color>,
+
+    /// Ticks and cardinal points are drawn on the large compass only
+    detailed: bool,
+}
+
+impl Compass {
+    /// Compass is large enough for the ticks and labels (in pixels)
+    const DETAILED_SIZE: u16 = 60;
+}
+
+impl<M> canvas::Program<M> for Compass {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        _cursor: mouse::Cursor,
+    ) -> Vec<canvas::Geometry> {
+        let mut frame = canvas::Frame::new(renderer, bounds.size());
+        let palette = theme.extended_palette();
+        let center = frame.center();
+        let radius = bounds.width.min(bounds.height) / 2. - 1.;
+        // Unit vector of the bearing (clockwise from the north, y is down)
+        let bearing = |deg: f32| {
+            let rad = deg.to_radians();
+            Vector::new(rad.sin(), -rad.cos())
+        };
+
+        let ring = canvas::Path::circle(center, radius);
+        frame.fill(&ring, palette.background.weak.color);
+        frame.stroke(
+            &ring,
+            canvas::Stroke::default()
+                .with_color(self.gust.unwrap_or(palette.background.strong.color))
+                .with_width(if self.gust.is_some() { 3. } else { 1. }),
+        );
+
+        if self.detailed {
+            for point in 0..16 {
+                let dir = bearing(point as f32 * 22.5);
+                let len = if point % 4 == 0 { 0.18 } else { 0.08 };
+                let tick = canvas::Path::line(
+                    center + dir * (radius * (1. - len)),
+                    center + dir * (radius - 2.),
+                );
+                frame.stroke(
+                    &tick,
+                    canvas::Stroke::default()
+                        .with_color(palette.background.strong.color)
+                        .with_width(1.),
+                );
+            }
+            for (label, deg) in [
+                ("wind-n", 0.),
+                ("wind-e", 90.),
+                ("wind-s", 180.),
+                ("wind-w", 270.),
+            ] {
+                frame.fill_text(canvas::Text {
+                    content: tr!(label),
+                    position: center + bearing(deg) * (radius * 0.62),
+                    color: palette.background.base.text,
+                    size: (radius * 0.28).into(),
+                    horizontal_alignment: iced::alignment::Horizontal::Center,
+                    vertical_alignment: iced::alignment::Vertical::Center,
+                    ..canvas::Text::default()
+                });
+            }
+        }
+
+        // The needle points where the wind blows to
+        let to = bearing(f32::from(self.deg % 360) + 180.);
+        let side = Vector::new(-to.y, to.x);
+        let head = center + to * (radius * 0.85);
+        let tail = center - to * (radius * 0.7);
+        let base = center + to * (radius * 0.35);
+        let width = radius * 0.22;
+
+        frame.stroke(
+            &canvas::Path::line(tail, base),
+            canvas::Stroke::default()
+                .with_color(self.color)
+                .with_width((radius * 0.1).max(1.5)),
+        );
+        frame.fill(
+            &canvas::Path::new(|path| {
+                path.move_to(head);
+                path.line_to(base + side * width);
+                path.line_to(base - side * width);
+                path.close();
+            }),
+            self.color,
+        );
+
+        vec![frame.into_geometry()]
+    }
+}
+
+/// Compass of the `size` with the needle showing the wind direction. The
+/// needle is colored according to the Beaufort force of the wind and the
+/// ring according to the force of the gusts
+pub fn compass<'a, M: 'a>(wind: &Wind, units: Units, size: u16) -> Element<'a, M> {
+    let color = |speed| BEAUFORT_COLORS[usize::from(beaufort(speed, units))];
+
+    canvas(Compass {
+        deg: wind.deg,
+        color: color(wind.speed),
+        gust: wind.gust.map(color),
+        detailed: size >= Compass::DETAILED_SIZE,
+    })
+    .width(size)
+    .height(size)
+    .into()
+}
+
+/// Layout of the on-screen keyboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeyboardMode {
+    #[default]
+    Lower,
+
+    /// Upper case for the next character
+    Upper,
+    Symbols,
+}
+
+/// Key of the on-screen keyboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Key {
+    Char(char),
+    Backspace,
+    Shift,
+
+    /// Switch between letters and symbols
+    Symbols,
+    Space,
+    Enter,
+}
+
+impl Key {
+    /// Applies the key to the edited `text`. Returns `true` if Enter was
+    /// pressed
+    pub fn apply(self, text: &mut String, mode: &mut KeyboardMode) -> bool {
+        match self {
+            Self::Char(c) => {
+                text.push(c);
+                if *mode == KeyboardMode::Upper {
+                    *mode = KeyboardMode::Lower;
+                }
+            }
+            Self::Backspace => {
+                text.pop();
+            }
+            Self::Shift => {
+                *mode = match mode {
+                    KeyboardMode::Lower => KeyboardMode::Upper,
+                    _ => KeyboardMode::Lower,
+                }
+            }
+            Self::Symbols => {
+                *mode = match mode {
+                    KeyboardMode::Symbols => KeyboardMode::Lower,
+                    _ => KeyboardMode::Symbols,
+                }
+            }
+            Self::Space => text.push(' '),
+            Self::Enter => return true,
+        }
+        false
+    }
+}
+
+impl KeyboardMode {
+    /// Rows of the character keys. Shift and Backspace are added to the
+    /// last one
+    fn rows(self) -> [&'static str; 4] {
+        match self {
+            Self::Lower => ["1234567890", "qwertyuiop", "asdfghjkl", "zxcvbnm"],
+            Self::Upper => ["1234567890", "QWERTYUIOP", "ASDFGHJKL", "ZXCVBNM"],
+            Self::Symbols => ["1234567890", "!@#$%^&*()", "-_=+[]{};:", "'\"\\|,.<>/?`~"],
+        }
+    }
+}
+
+/// Width of the key which fits the longest row of all layouts into the
+/// screen, so the keys don't change their size with the layout
+fn key_width(max_width: f32, spacing: f32) -> f32 {
+    [
+        KeyboardMode::Lower,
+        KeyboardMode::Upper,
+        KeyboardMode::Symbols,
+    ]
+    .into_iter()
+    .flat_map(|mode| mode.rows().into_iter().enumerate())
+    .map(|(i, chars)| {
+        let keys = chars.chars().count() as f32;
+        // Shift and Backspace are 1.5 keys wide
+        let (units, keys) = if i == 3 {
+            (keys + 3., keys + 2.)
+        } else {
+            (keys, keys)
+        };
+        (max_width - spacing * (keys - 1.)) / units
+    })
+    .fold(f32::INFINITY, f32::min)
+}
+
+/// Touch keyboard for the text input on devices without physical keyboard
+pub fn keyboard<'a, M: Clone + 'a>(
+    mode: KeyboardMode,
+    on_key: impl Fn(Key) -> M + 'a,
+) -> Element<'a, M> {
+    /// Pages have 10 px padding on both sides
+    const MAX_WIDTH: f32 = DEFAULT_WIN_SIZE.0 as f32 - 20.;
+    const MAX_KEY_WIDTH: f32 = 58.;
+    const KEY_HEIGHT: f32 = 42.;
+    const SPACING: f32 = 4.;
+    const TEXT_SIZE: u16 = 20;
+
+    let key_width = key_width(MAX_WIDTH, SPACING).min(MAX_KEY_WIDTH);
+    let rows = mode.rows();
+
+    let key = |label: String, key: Key, width: f32| {
+        button(text(label).size(TEXT_SIZE).center())
+            .on_press(on_key(key))
+            .width(width)
+            .height(KEY_HEIGHT)
+            .style(if matches!(key, Key::Char(_) | Key::Space) {
+                button::secondary
+            } else {
+                button::primary
+            })
+    };
+
+    let mut keys = column![].spacing(SPACING).align_x(Center);
+    for (i, chars) in rows.iter().enumerate() {
+        let mut line = row![].spacing(SPACING);
+        if i == 3 {
+            line = line.push(key("Shift".to_string(), Key::Shift, key_width * 1.5));
+        }
+        for c in chars.chars() {
+            line = line.push(key(c.to_string(), Key::Char(c), key_width));
+        }
+        if i == 3 {
+            line = line.push(key(
+                tr!("keyboard-backspace"),
+                Key::Backspace,
+                key_width * 1.5,
+            ));
+        }
+        keys = keys.push(line);
+    }
+
+    keys.push(
+        row![
+            key(
+                if mode == KeyboardMode::Symbols {
+                    "abc"
+                } else {
+                    "?123"
+                }
+                .to_string(),
+                Key::Symbols,
+                key_width * 1.5,
+            ),
+            key(tr!("keyboard-space"), Key::Space, key_width * 6.),
+            key("OK".to_string(), Key::Enter, key_width * 1.5),
+        ]
+        .spacing(SPACING),
+    )
+    .width(Length::Shrink)
+    .into()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn key_width_test() {
+        let width = key_width(780., 4.);
+        // Symbols: Shift + 12 keys + Backspace
+        let symbols = width * (12. + 3.) + 4. * 13.;
+        assert!(symbols <= 780. + 0.01);
+        assert!(symbols > 770.);
+    }
+}
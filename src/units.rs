@@ -2,7 +2,9 @@
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Deserialize, Serialize, Default, Clone, Copy)]
+use crate::i18n::tr;
+
+#[derive(Debug, Deserialize, Serialize, Default, Clone, Copy, PartialEq, Eq)]
 pub enum Units {
     #[serde(rename = "imperial")]
     Imperial,
@@ -12,13 +14,16 @@ pub enum Units {
     Metric,
 }
 
-impl ToString for Units {
-    fn to_string(&self) -> String {
-        match self {
-            Self::Imperial => "imperial",
-            Self::Metric => "metric",
-        }
-        .to_string()
+impl std::fmt::Display for Units {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Imperial => "imperial",
+                Self::Metric => "metric",
+            }
+        )
     }
 }
 
@@ -28,15 +33,15 @@ pub enum Variant {
 }
 
 impl Variant {
-    pub fn to_str(&self, units: Units) -> &str {
+    pub fn to_str(&self, units: Units) -> String {
         match self {
             Self::Degrees => match units {
-                Units::Imperial => "°F",
-                Units::Metric => "°C",
+                Units::Imperial => "°F".to_string(),
+                Units::Metric => "°C".to_string(),
             },
             Self::Speed => match units {
-                Units::Imperial => "km/h",
-                Units::Metric => "m/s",
+                Units::Imperial => tr!("unit-mph"),
+                Units::Metric => tr!("unit-meters-per-second"),
             },
         }
     }